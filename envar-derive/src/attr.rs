@@ -0,0 +1,844 @@
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Expr, ExprArray, ExprLit, Ident, Lit, LitInt, LitStr, Meta, MetaNameValue,
+    NestedMeta, Path, Token,
+};
+
+// Options set on the struct itself with `#[envar(...)]`
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) prefix: String,
+    pub(crate) dotenv: Option<String>,
+    pub(crate) derive_debug: bool,
+    pub(crate) derive_default: bool,
+    pub(crate) display: bool,
+    pub(crate) trim_all: bool,
+    pub(crate) empty_is_missing: bool,
+    pub(crate) file_fallback: bool,
+    pub(crate) expand: bool,
+    pub(crate) rename_all: RenameRule,
+    pub(crate) singleton: bool,
+    pub(crate) as_map: bool,
+    // The values are handed to the struct's `serde::Deserialize` implementation
+    pub(crate) serde: bool,
+    // The error type of `try_new()` and `reload()`, converted from `EnvarError` with `From`
+    pub(crate) error: Option<syn::Type>,
+    // Every variable is logged as it is resolved, without its value
+    pub(crate) trace: bool,
+}
+
+// How variable names are derived from field names that have no explicit `env` name
+#[derive(Clone, Copy, Default)]
+pub(crate) enum RenameRule {
+    // `dbHost` becomes `DBHOST`, the behavior from before `rename_all` existed
+    #[default]
+    Uppercase,
+    Lowercase,
+    ScreamingSnakeCase,
+    SnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    const NAMES: &'static [(&'static str, RenameRule)] = &[
+        ("UPPERCASE", RenameRule::Uppercase),
+        ("lowercase", RenameRule::Lowercase),
+        ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnakeCase),
+        ("snake_case", RenameRule::SnakeCase),
+        ("kebab-case", RenameRule::KebabCase),
+        ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebabCase),
+    ];
+
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        let value = lit.value();
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, rule)| *rule)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::NAMES.iter().map(|(name, _)| *name).collect();
+                syn::Error::new_spanned(
+                    lit,
+                    format!(
+                        "unknown rename_all rule `{}`, expected one of: {}",
+                        value,
+                        names.join(", ")
+                    ),
+                )
+            })
+    }
+
+    // Applies the rule to a field name, splitting words on underscores and lowercase to uppercase changes
+    pub(crate) fn apply(self, field_name: &Ident) -> String {
+        let field_name = field_name.unraw().to_string();
+        let mut words: Vec<String> = Vec::new();
+        for part in field_name.split('_').filter(|part| !part.is_empty()) {
+            let mut word = String::new();
+            let mut previous_lowercase = false;
+            for c in part.chars() {
+                if c.is_uppercase() && previous_lowercase {
+                    words.push(std::mem::take(&mut word));
+                }
+                previous_lowercase = c.is_lowercase() || c.is_ascii_digit();
+                word.push(c);
+            }
+            words.push(word);
+        }
+        let join = |separator: &str, upper: bool| {
+            words
+                .iter()
+                .map(|word| {
+                    if upper {
+                        word.to_uppercase()
+                    } else {
+                        word.to_lowercase()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            RenameRule::Uppercase => field_name.to_uppercase(),
+            RenameRule::Lowercase => field_name.to_lowercase(),
+            RenameRule::ScreamingSnakeCase => join("_", true),
+            RenameRule::SnakeCase => join("_", false),
+            RenameRule::KebabCase => join("-", false),
+            RenameRule::ScreamingKebabCase => join("-", true),
+        }
+    }
+}
+
+pub(crate) fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("envar")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected a list of options, like #[envar(prefix = \"APP_\")]",
+                ))
+            }
+        };
+        for nested in list.nested {
+            let meta = match nested {
+                NestedMeta::Meta(meta @ (Meta::Path(_) | Meta::NameValue(_))) => meta,
+                nested => return Err(unknown_container_key(&nested)),
+            };
+            let key = meta.path().get_ident().map(Ident::to_string);
+            match (key.as_deref(), &meta) {
+                (Some("derive_debug"), Meta::Path(_)) => container.derive_debug = true,
+                (Some("derive_default"), Meta::Path(_)) => container.derive_default = true,
+                (Some("display"), Meta::Path(_)) => container.display = true,
+                (Some("trim_all"), Meta::Path(_)) => container.trim_all = true,
+                (Some("empty_is_missing"), Meta::Path(_)) => container.empty_is_missing = true,
+                (Some("file_fallback"), Meta::Path(_)) => container.file_fallback = true,
+                (Some("expand"), Meta::Path(_)) => container.expand = true,
+                (Some("singleton"), Meta::Path(_)) => container.singleton = true,
+                (Some("as_map"), Meta::Path(_)) => container.as_map = true,
+                (Some("serde"), Meta::Path(_)) => container.serde = true,
+                (Some("trace"), Meta::Path(_)) => container.trace = true,
+                (Some(key @ ("prefix" | "dotenv")), Meta::NameValue(meta)) => match &meta.lit {
+                    Lit::Str(lit) if key == "prefix" => container.prefix = lit.value(),
+                    Lit::Str(lit) => container.dotenv = Some(lit.value()),
+                    lit => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            format!("`{}` expects a string value", key),
+                        ))
+                    }
+                },
+                (Some("error"), Meta::NameValue(meta)) => match &meta.lit {
+                    Lit::Str(lit) => container.error = Some(lit.parse()?),
+                    lit => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            "expected the name of an error type",
+                        ))
+                    }
+                },
+                (Some("rename_all"), Meta::NameValue(meta)) => match &meta.lit {
+                    Lit::Str(lit) => container.rename_all = RenameRule::from_lit(lit)?,
+                    lit => {
+                        return Err(syn::Error::new_spanned(
+                            lit,
+                            "expected a rename_all rule string",
+                        ))
+                    }
+                },
+                (Some(key), Meta::NameValue(meta)) if CONTAINER_FLAGS.contains(&key) => {
+                    return Err(syn::Error::new_spanned(
+                        &meta.lit,
+                        format!("`{}` is a flag and takes no value", key),
+                    ))
+                }
+                (Some(key), Meta::Path(path)) if CONTAINER_KEYS.contains(&key) => {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        format!("`{}` needs a value, like `{} = ...`", key, key),
+                    ))
+                }
+                _ => return Err(unknown_container_key(meta.path())),
+            }
+        }
+    }
+    Ok(container)
+}
+
+// The keys accepted inside `#[envar(...)]` on the struct, listed in the error for an unknown key
+const CONTAINER_KEYS: &[&str] = &[
+    "as_map",
+    "derive_debug",
+    "derive_default",
+    "display",
+    "dotenv",
+    "empty_is_missing",
+    "error",
+    "expand",
+    "file_fallback",
+    "prefix",
+    "rename_all",
+    "serde",
+    "singleton",
+    "trace",
+    "trim_all",
+];
+
+// The keys of `#[envar(...)]` on the struct that are flags and take no value
+const CONTAINER_FLAGS: &[&str] = &[
+    "as_map",
+    "derive_debug",
+    "derive_default",
+    "display",
+    "empty_is_missing",
+    "expand",
+    "file_fallback",
+    "serde",
+    "singleton",
+    "trace",
+    "trim_all",
+];
+
+fn unknown_container_key(tokens: impl ToTokens) -> syn::Error {
+    syn::Error::new_spanned(
+        tokens,
+        format!(
+            "unknown envar attribute on a struct, expected one of: {}",
+            CONTAINER_KEYS.join(", ")
+        ),
+    )
+}
+
+// Options set on a field with `#[env = "..."]`, `#[env(...)]`, `#[envar(...)]` and `#[default = "..."]`
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) name: Option<String>,
+    // The fallback value is kept as a string so it goes through the same parse path as the variable
+    pub(crate) default: Option<String>,
+    // The separator of list elements, a comma unless set
+    pub(crate) delimiter: Option<String>,
+    // List elements are separated by runs of whitespace instead of a delimiter
+    pub(crate) split_whitespace: bool,
+    // The field is itself an Envar struct and is read with its own loader
+    pub(crate) flatten: bool,
+    // The value is redacted from generated `Debug` output
+    pub(crate) secret: bool,
+    // A `fn(&str) -> Result<T, E>` used instead of `FromStr`
+    pub(crate) parse_with: Option<Path>,
+    // Fallback variable names, tried in order after the primary one
+    pub(crate) aliases: Vec<String>,
+    // Surrounding whitespace is removed; a blank value counts as unset
+    pub(crate) trim: bool,
+    // Any of these characters are stripped from both ends of the value, after `trim`
+    pub(crate) trim_matches: Option<String>,
+    // A sample value written to the generated `.env` template
+    pub(crate) example: Option<String>,
+    // `<NAME>_FILE` is checked first and names a file holding the value
+    pub(crate) file_fallback: bool,
+    // `${OTHER}` references in the value are substituted
+    pub(crate) expand: bool,
+    // Inclusive bounds checked after parsing, in the field's own type
+    pub(crate) min: Option<Expr>,
+    pub(crate) max: Option<Expr>,
+    // Inclusive bounds on the length of string values
+    pub(crate) min_len: Option<Expr>,
+    pub(crate) max_len: Option<Expr>,
+    // A regex string values must match
+    pub(crate) pattern: Option<LitStr>,
+    // A chrono format string for date and time fields
+    pub(crate) format: Option<LitStr>,
+    // The base integers are written in, one of 2, 8, 10 or 16
+    pub(crate) radix: Option<LitInt>,
+    // The value is a byte count with an optional unit suffix like `MB` or `KiB`
+    pub(crate) bytesize: bool,
+    // The unit of a `bytesize` value that is a bare number
+    pub(crate) default_unit: Option<String>,
+    // The field isn't read at all and is set to `Default::default()`
+    pub(crate) skip: bool,
+    // A `bool` that is true whenever the variable is set, whatever its value
+    pub(crate) flag: bool,
+    // A `fn(String) -> String` applied to the raw value before it is parsed
+    pub(crate) transform: Option<Path>,
+    // The value is base64 and decodes into a `Vec<u8>`
+    pub(crate) base64: bool,
+    // The value is JSON, deserialized with serde
+    pub(crate) json: bool,
+    // A `fn() -> T` producing the value when the variable isn't set
+    pub(crate) default_fn: Option<Path>,
+    // A constant used as is when the variable isn't set
+    pub(crate) default_const: Option<Path>,
+    // An expression computing the value from the fields declared before this one
+    pub(crate) default_expr: Option<Expr>,
+    // A function checking the parsed value, returning why it is invalid
+    pub(crate) validate: Option<Path>,
+    // Another variable that makes this optional field required when it is true
+    pub(crate) required_if: Option<String>,
+    // A migration hint printed when the value comes from a deprecated name
+    pub(crate) deprecated: Option<String>,
+    // The message reported instead of the usual one when a required variable is missing
+    pub(crate) error: Option<String>,
+    // Floats may be NaN or infinite
+    pub(crate) allow_nonfinite: bool,
+    // The value is a number or a repeated character, like `vvv` for 3
+    pub(crate) count: bool,
+    // An optional field whose value fails to parse is `None` instead of an error
+    pub(crate) lenient: bool,
+    // A `OnceLock<T>` field left empty, with an accessor that reads it on first call
+    pub(crate) lazy: bool,
+    // The path must exist: `any`, or a `dir` or `file` in particular
+    pub(crate) must_exist: Option<String>,
+    // A `Duration` given as a whole number of seconds or milliseconds
+    pub(crate) secs: bool,
+    pub(crate) millis: bool,
+    // Another variable that must be true for this field to be read at all
+    pub(crate) requires: Option<String>,
+    // The value is converted to lowercase or uppercase before it is parsed
+    pub(crate) lowercase: bool,
+    pub(crate) uppercase: bool,
+    // Values that mean an optional field is explicitly unset, compared case-insensitively
+    pub(crate) none_values: Vec<String>,
+    // A `Vec` of Envar structs, counted by the field's variable and read with an indexed prefix each
+    pub(crate) indexed_list: bool,
+    // The prefix of the indexed items, before the index, which defaults to the count variable and `_`
+    pub(crate) item_prefix: Option<String>,
+    // The prefix a flattened struct is read through, where `Some(None)` derives it from the field name
+    pub(crate) flatten_prefix: Option<Option<String>>,
+    // The key the field is looked up by in the args of `from_env_and_args()`, instead of its variable name
+    pub(crate) arg: Option<String>,
+    // Reads the variable of another field, for a second view of the same value
+    pub(crate) shared: bool,
+    // The field's variable holds the path of a file, whose contents are the value
+    pub(crate) path_from: bool,
+    // A float given as a ratio like `0.1` or a percentage like `10%`
+    pub(crate) percent: bool,
+    // What an empty value means for this field: `error`, `default` or `none`
+    pub(crate) when_empty: Option<String>,
+    // Where each key was written, so that conflicts between keys point at the offending one
+    spans: Vec<(String, proc_macro2::Span)>,
+}
+
+impl FieldAttrs {
+    // The span of `key` in the field's attributes
+    fn span(&self, key: &str) -> proc_macro2::Span {
+        self.spans
+            .iter()
+            .find(|(name, _)| name == key)
+            .map_or_else(proc_macro2::Span::call_site, |(_, span)| *span)
+    }
+}
+
+pub(crate) fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field = FieldAttrs::default();
+    for attr in attrs {
+        match attr.parse_meta() {
+            // A bare `#[env]` marks the field without changing its derived name
+            Ok(Meta::Path(path)) if path.is_ident("env") => {}
+            Ok(Meta::NameValue(meta)) if meta.path.is_ident("env") => match meta.lit {
+                Lit::Str(lit) => set_name(&mut field, &lit, lit.value())?,
+                lit => {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "expected a variable name string",
+                    ))
+                }
+            },
+            // Lists like `aliases = ["A", "B"]` aren't valid meta, so `env(...)` has its own parser
+            _ if attr.path.is_ident("env") => {
+                let args =
+                    attr.parse_args_with(Punctuated::<EnvArg, Token![,]>::parse_terminated)?;
+                apply_env_args(&mut field, args)?;
+            }
+            Ok(Meta::NameValue(meta)) if meta.path.is_ident("default") => match meta.lit {
+                Lit::Str(lit) => field.default = Some(lit.value()),
+                lit => {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "expected a default value string",
+                    ))
+                }
+            },
+            Ok(Meta::List(list)) if list.path.is_ident("envar") => {
+                for nested in list.nested {
+                    if let NestedMeta::Meta(meta) = &nested {
+                        if let Some(key) = meta.path().get_ident() {
+                            field.spans.push((key.to_string(), key.span()));
+                        }
+                    }
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("flatten") => {
+                            field.flatten = true;
+                        }
+                        // The count variable doubles as the field's variable name
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(lit),
+                            ..
+                        })) if path.is_ident("indexed_list") => {
+                            set_name(&mut field, &lit, lit.value())?;
+                            field.indexed_list = true;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(lit),
+                            ..
+                        })) if path.is_ident("item_prefix") => {
+                            field.item_prefix = Some(lit.value());
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(lit),
+                            ..
+                        })) if path.is_ident("prefix") => {
+                            field.flatten_prefix = Some(Some(lit.value()));
+                        }
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("prefix") => {
+                            field.flatten_prefix = Some(None);
+                        }
+                        nested => {
+                            return Err(syn::Error::new_spanned(
+                                nested,
+                                "unknown envar attribute on a field, expected `flatten`, `prefix`, `indexed_list` or `item_prefix`",
+                            ))
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if field.lowercase && field.uppercase {
+        return Err(syn::Error::new(
+            field.span("uppercase"),
+            "a field can be either `lowercase` or `uppercase`, not both",
+        ));
+    }
+    if field.flatten_prefix.is_some() && !field.flatten {
+        return Err(syn::Error::new(
+            field.span("prefix"),
+            "`prefix` can only be used together with `flatten`",
+        ));
+    }
+    if field.path_from && field.file_fallback {
+        return Err(syn::Error::new(
+            field.span("path_from"),
+            "a field can have either `path_from` or `file_fallback`, not both",
+        ));
+    }
+    if field.split_whitespace && field.delimiter.is_some() {
+        return Err(syn::Error::new(
+            field.span("split_whitespace"),
+            "a field can have either a `delimiter` or `split_whitespace`, not both",
+        ));
+    }
+    if field.default_unit.is_some() && !field.bytesize {
+        return Err(syn::Error::new(
+            field.span("default_unit"),
+            "`default_unit` can only be used together with `bytesize`",
+        ));
+    }
+    if field.item_prefix.is_some() && !field.indexed_list {
+        return Err(syn::Error::new(
+            field.span("item_prefix"),
+            "`item_prefix` can only be used together with `indexed_list`",
+        ));
+    }
+    if let (Some(_), Some(default_fn)) = (&field.default, &field.default_fn) {
+        return Err(syn::Error::new_spanned(
+            default_fn,
+            "a field can have either a `default` or a `default_fn`, not both",
+        ));
+    }
+    if let Some(default_const) = &field.default_const {
+        if field.default.is_some() || field.default_fn.is_some() {
+            return Err(syn::Error::new_spanned(
+                default_const,
+                "a field can have only one of `default`, `default_fn`, `default_const` and `default_expr`",
+            ));
+        }
+    }
+    if let Some(default_expr) = &field.default_expr {
+        if field.default.is_some() || field.default_fn.is_some() || field.default_const.is_some() {
+            return Err(syn::Error::new_spanned(
+                default_expr,
+                "a field can have only one of `default`, `default_fn`, `default_const` and `default_expr`",
+            ));
+        }
+    }
+    Ok(field)
+}
+
+// The keys accepted inside `#[env(...)]`, listed in the error for an unknown key
+const ENV_KEYS: &[&str] = &[
+    "aliases",
+    "allow_nonfinite",
+    "arg",
+    "base64",
+    "bytesize",
+    "count",
+    "default",
+    "default_const",
+    "default_expr",
+    "default_fn",
+    "default_unit",
+    "delimiter",
+    "deprecated",
+    "error",
+    "example",
+    "expand",
+    "file_fallback",
+    "flag",
+    "format",
+    "json",
+    "lazy",
+    "lenient",
+    "lowercase",
+    "max",
+    "max_len",
+    "millis",
+    "min",
+    "min_len",
+    "must_exist",
+    "name",
+    "none_values",
+    "parse_with",
+    "path_from",
+    "pattern",
+    "percent",
+    "radix",
+    "required_if",
+    "requires",
+    "secret",
+    "secs",
+    "shared",
+    "size",
+    "skip",
+    "split_whitespace",
+    "transform",
+    "trim",
+    "trim_matches",
+    "uppercase",
+    "validate",
+    "when_empty",
+];
+
+// The keys of `#[env(...)]` that are flags and take no value
+const ENV_FLAGS: &[&str] = &[
+    "allow_nonfinite",
+    "base64",
+    "bytesize",
+    "count",
+    "expand",
+    "file_fallback",
+    "flag",
+    "json",
+    "lazy",
+    "lenient",
+    "lowercase",
+    "millis",
+    "percent",
+    "secret",
+    "secs",
+    "shared",
+    "size",
+    "skip",
+    "split_whitespace",
+    "trim",
+    "uppercase",
+];
+
+// Applies the entries of an `#[env(...)]` list to the field options
+fn apply_env_args(field: &mut FieldAttrs, args: Punctuated<EnvArg, Token![,]>) -> syn::Result<()> {
+    for arg in args {
+        let key = match arg.key.to_string() {
+            // `size` is another spelling of `bytesize`
+            key if key == "size" => "bytesize".to_string(),
+            key => key,
+        };
+        field.spans.push((key.clone(), arg.key.span()));
+        match (key.as_str(), &arg.value) {
+            ("secret", None) => field.secret = true,
+            ("trim", None) => field.trim = true,
+            ("file_fallback", None) => field.file_fallback = true,
+            ("expand", None) => field.expand = true,
+            ("bytesize", None) => field.bytesize = true,
+            ("skip", None) => field.skip = true,
+            ("shared", None) => field.shared = true,
+            ("split_whitespace", None) => field.split_whitespace = true,
+            ("percent", None) => field.percent = true,
+            ("flag", None) => field.flag = true,
+            ("base64", None) => field.base64 = true,
+            ("json", None) => field.json = true,
+            ("allow_nonfinite", None) => field.allow_nonfinite = true,
+            ("count", None) => field.count = true,
+            ("lenient", None) => field.lenient = true,
+            ("lazy", None) => field.lazy = true,
+            ("lowercase", None) => field.lowercase = true,
+            ("uppercase", None) => field.uppercase = true,
+            ("secs", None) => field.secs = true,
+            ("millis", None) => field.millis = true,
+            ("must_exist", None) => field.must_exist = Some("any".to_string()),
+            ("must_exist", Some(value)) => {
+                match str_value(value).as_deref() {
+                    Some(kind @ ("dir" | "file")) => field.must_exist = Some(kind.to_string()),
+                    _ => return Err(syn::Error::new_spanned(
+                        value,
+                        "expected `must_exist`, `must_exist = \"dir\"` or `must_exist = \"file\"`",
+                    )),
+                }
+            }
+            ("default_unit", Some(value)) => {
+                let unit = str_arg(&arg.key, value)?;
+                if !is_byte_unit(&unit) {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected a unit such as \"KB\", \"MiB\" or \"G\"",
+                    ));
+                }
+                field.default_unit = Some(unit);
+            }
+            ("trim_matches", Some(value)) => {
+                let chars = str_arg(&arg.key, value)?;
+                if chars.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "`trim_matches` needs at least one character to strip",
+                    ));
+                }
+                field.trim_matches = Some(chars);
+            }
+            ("when_empty", Some(value)) => match str_value(value).as_deref() {
+                Some(policy @ ("error" | "default" | "none")) => {
+                    field.when_empty = Some(policy.to_string())
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected `when_empty = \"error\"`, `\"default\"` or `\"none\"`",
+                    ))
+                }
+            },
+            ("name", Some(value)) => set_name(field, value, str_arg(&arg.key, value)?)?,
+            ("default", Some(value)) => field.default = Some(str_arg(&arg.key, value)?),
+            ("delimiter", Some(value)) => field.delimiter = Some(str_arg(&arg.key, value)?),
+            ("parse_with", Some(value)) => field.parse_with = Some(path_arg(&arg.key, value)?),
+            ("transform", Some(value)) => field.transform = Some(path_arg(&arg.key, value)?),
+            ("default_fn", Some(value)) => field.default_fn = Some(path_arg(&arg.key, value)?),
+            ("default_const", Some(Expr::Path(path))) => {
+                field.default_const = Some(path.path.clone())
+            }
+            ("default_const", Some(value)) => {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    "`default_const` expects the path of a constant, like `default_const = DEFAULT_PORT`",
+                ))
+            }
+            ("default_expr", Some(value)) => match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => field.default_expr = Some(lit.parse()?),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "`default_expr` expects an expression in a string, like `default_expr = \"port + 1\"`",
+                    ))
+                }
+            },
+            ("validate", Some(value)) => field.validate = Some(path_arg(&arg.key, value)?),
+            ("min", Some(value)) => field.min = Some(value.clone()),
+            ("max", Some(value)) => field.max = Some(value.clone()),
+            ("min_len", Some(value)) => field.min_len = Some(value.clone()),
+            ("max_len", Some(value)) => field.max_len = Some(value.clone()),
+            ("pattern", Some(value)) => match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => field.pattern = Some(lit.clone()),
+                _ => return Err(syn::Error::new_spanned(value, "expected a regex string")),
+            },
+            ("format", Some(value)) => match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => field.format = Some(lit.clone()),
+                _ => return Err(syn::Error::new_spanned(value, "expected a format string")),
+            },
+            ("radix", Some(value)) => match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(lit), ..
+                }) if matches!(lit.base10_parse::<u32>(), Ok(2 | 8 | 10 | 16)) => {
+                    field.radix = Some(lit.clone())
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected a radix of 2, 8, 10 or 16",
+                    ))
+                }
+            },
+            ("required_if", Some(value)) => field.required_if = Some(str_arg(&arg.key, value)?),
+            ("requires", Some(value)) => field.requires = Some(str_arg(&arg.key, value)?),
+            ("arg", Some(value)) => field.arg = Some(str_arg(&arg.key, value)?),
+            // The path variable doubles as the field's variable name
+            ("path_from", Some(value)) => {
+                set_name(field, value, str_arg(&arg.key, value)?)?;
+                field.path_from = true;
+            }
+            ("deprecated", Some(value)) => field.deprecated = Some(str_arg(&arg.key, value)?),
+            ("error", Some(value)) => field.error = Some(str_arg(&arg.key, value)?),
+            ("example", Some(value)) => field.example = Some(str_arg(&arg.key, value)?),
+            ("none_values", Some(value)) => {
+                field.none_values = str_list_value(value).ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        value,
+                        "expected a list of values, like [\"none\", \"null\"]",
+                    )
+                })?;
+            }
+            ("aliases", Some(value)) => {
+                field.aliases = str_list_value(value).ok_or_else(|| {
+                    syn::Error::new_spanned(value, "expected a list of names, like [\"A\", \"B\"]")
+                })?;
+                if let Some((element, alias)) = repeated_element(value) {
+                    return Err(syn::Error::new_spanned(
+                        element,
+                        format!("alias `{}` is listed twice", alias),
+                    ));
+                }
+            }
+            (key, Some(value)) if ENV_FLAGS.contains(&key) => {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    format!("`{}` is a flag and takes no value", key),
+                ))
+            }
+            (key, None) if ENV_KEYS.contains(&key) => {
+                return Err(syn::Error::new_spanned(
+                    &arg.key,
+                    format!("`{}` needs a value, like `{} = ...`", key, key),
+                ))
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &arg.key,
+                    format!(
+                        "unknown env attribute `{}`, expected one of: {}",
+                        key,
+                        ENV_KEYS.join(", ")
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+// Sets the variable name, which can be given once as `#[env = "..."]` or `#[env(name = "...")]`
+fn set_name(field: &mut FieldAttrs, span: &impl ToTokens, name: String) -> syn::Result<()> {
+    if field.name.is_some() {
+        return Err(syn::Error::new_spanned(
+            span,
+            "the variable name is already set for this field",
+        ));
+    }
+    field.name = Some(name);
+    Ok(())
+}
+
+// Whether `unit` is one of the units `bytesize` values accept, matching them the same way
+fn is_byte_unit(unit: &str) -> bool {
+    let unit = unit.strip_suffix(['B', 'b']).unwrap_or(unit);
+    ["", "k", "m", "g", "t", "ki", "mi", "gi", "ti"].contains(&unit.to_ascii_lowercase().as_str())
+}
+
+// A single `key` or `key = value` entry of an `#[env(...)]` list.
+// Values are parsed as expressions so that lists like `aliases = ["A", "B"]` are accepted.
+struct EnvArg {
+    key: Ident,
+    value: Option<Expr>,
+}
+
+impl Parse for EnvArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Accept keywords too, so keys like `enum` can be used
+        let key = input.call(Ident::parse_any)?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(EnvArg { key, value })
+    }
+}
+
+// The string value of `key`, or an error pointing at the value
+fn str_arg(key: &Ident, expr: &Expr) -> syn::Result<String> {
+    str_value(expr)
+        .ok_or_else(|| syn::Error::new_spanned(expr, format!("`{}` expects a string value", key)))
+}
+
+// The function path in the string value of `key`, spanned to the string for error messages
+fn path_arg(key: &Ident, expr: &Expr) -> syn::Result<Path> {
+    let invalid =
+        || syn::Error::new_spanned(expr, format!("`{}` expects the path of a function", key));
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => lit.parse().map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+fn str_value(expr: &Expr) -> Option<String> {
+    if let Expr::Lit(ExprLit {
+        lit: Lit::Str(lit), ..
+    }) = expr
+    {
+        return Some(lit.value());
+    }
+    None
+}
+
+fn str_list_value(expr: &Expr) -> Option<Vec<String>> {
+    if let Expr::Array(ExprArray { elems, .. }) = expr {
+        return elems.iter().map(str_value).collect();
+    }
+    None
+}
+
+// Finds the first string in a list value that repeats an earlier one
+fn repeated_element(expr: &Expr) -> Option<(&Expr, String)> {
+    if let Expr::Array(ExprArray { elems, .. }) = expr {
+        let mut seen = Vec::new();
+        for element in elems {
+            let value = str_value(element)?;
+            if seen.contains(&value) {
+                return Some((element, value));
+            }
+            seen.push(value);
+        }
+    }
+    None
+}
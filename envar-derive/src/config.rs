@@ -0,0 +1,109 @@
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_quote, DeriveInput, Ident, Lit, Token, Type};
+
+use crate::derive_envar;
+
+// The body of `config! { NAME: Type = default, ... }`
+pub(crate) struct ConfigInput {
+    entries: Punctuated<ConfigEntry, Token![,]>,
+}
+
+impl Parse for ConfigInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(ConfigInput {
+            entries: Punctuated::parse_terminated(input)?,
+        })
+    }
+}
+
+// A single `NAME: Type` entry, with an optional `= default`
+struct ConfigEntry {
+    var: Ident,
+    ty: Type,
+    // The `-` of a negative number default
+    minus: Option<Token![-]>,
+    default: Option<Lit>,
+}
+
+impl Parse for ConfigEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let var = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        let (mut minus, mut default) = (None, None);
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            minus = input.parse()?;
+            default = Some(input.parse()?);
+        }
+        Ok(ConfigEntry {
+            var,
+            ty,
+            minus,
+            default,
+        })
+    }
+}
+
+// Generates a block that declares a struct with one field per entry, derives `Envar` for it
+// and evaluates to the result of `try_new()`
+pub(crate) fn expand_config(input: ConfigInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut fields = Vec::new();
+    let mut declared = Vec::new();
+    for entry in &input.entries {
+        let var = entry.var.to_string();
+        let field = field_ident(&entry.var)?;
+        let ty = &entry.ty;
+        let sign = if entry.minus.is_some() { "-" } else { "" };
+        let default = match &entry.default {
+            None => None,
+            Some(Lit::Int(lit)) => Some(format!("{}{}", sign, lit.base10_digits())),
+            Some(Lit::Float(lit)) => Some(format!("{}{}", sign, lit.base10_digits())),
+            Some(_) if entry.minus.is_some() => {
+                return Err(syn::Error::new_spanned(
+                    entry.minus,
+                    "only a number default can be negative",
+                ))
+            }
+            Some(Lit::Str(lit)) => Some(lit.value()),
+            Some(Lit::Bool(lit)) => Some(lit.value.to_string()),
+            Some(lit) => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "expected a string, number or boolean default",
+                ))
+            }
+        }
+        .map(|default| quote! { #[default = #default] });
+        fields.push(quote! { #[env = #var] #default #field: #ty });
+        declared.push(quote! { #field: #ty });
+    }
+
+    let name = format_ident!("Config");
+    let derive_input: DeriveInput = parse_quote! {
+        struct #name { #(#fields),* }
+    };
+    let derived = derive_envar(derive_input)?;
+    Ok(quote! {{
+        #[allow(dead_code)]
+        struct #name { #(#declared),* }
+
+        #derived
+
+        #name::try_new()
+    }})
+}
+
+// The field for a variable is its name in lowercase, raw so that names like `TYPE` still work
+fn field_ident(var: &Ident) -> syn::Result<Ident> {
+    let name = var.to_string().to_lowercase();
+    if matches!(name.as_str(), "self" | "super" | "crate") {
+        return Err(syn::Error::new_spanned(
+            var,
+            format!("`{}` can't be used as a config variable name", var),
+        ));
+    }
+    Ok(Ident::new_raw(&name, var.span()))
+}
@@ -0,0 +1,61 @@
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+// Generates a case-insensitive `FromStr` implementation matching values against variant names
+pub(crate) fn derive_envar_enum(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "EnvarEnum can only be derived for enums",
+            ))
+        }
+    };
+    if variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "EnvarEnum needs at least one variant to select",
+        ));
+    }
+    let mut arms = Vec::new();
+    let mut accepted: Vec<String> = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "EnvarEnum variants can't carry fields, they are selected by name",
+            ));
+        }
+        let ident = &variant.ident;
+        let lowercase = ident.to_string().to_lowercase();
+        // Values are matched case-insensitively, so the later variant could never be selected
+        if let Some(index) = accepted.iter().position(|other| *other == lowercase) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!(
+                    "variants `{}` and `{}` both match the value `{}`",
+                    variants[index].ident, ident, lowercase
+                ),
+            ));
+        }
+        arms.push(quote! { #lowercase => ::core::result::Result::Ok(Self::#ident), });
+        accepted.push(lowercase);
+    }
+    let expected = format!("expected one of {}", accepted.join(", "));
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::std::string::String;
+
+            fn from_str(value: &str) -> ::core::result::Result<Self, Self::Err> {
+                match value.to_lowercase().as_str() {
+                    #(#arms)*
+                    _ => ::core::result::Result::Err(#expected.to_string()),
+                }
+            }
+        }
+    })
+}
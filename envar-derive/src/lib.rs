@@ -0,0 +1,4187 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Field, Fields, GenericArgument, Generics, Ident, Member,
+    PathArguments, Token, Type,
+};
+
+mod attr;
+mod config;
+mod enums;
+
+use attr::{parse_container_attrs, parse_field_attrs, ContainerAttrs, FieldAttrs};
+
+/// # Envar
+/// A derive macro to automatically parse environment variables into a struct.
+/// The macro will look for environment variables with the same name as the struct fields.
+/// The variables are read at runtime, when `new()` is called.
+/// If a required environment variable is not found, `new()` will panic.
+/// Use `try_new()` instead to get an `envar::EnvarError` back.
+///
+/// ## Example
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///    #[env = "DB_CONNECTION_PORT"]
+///    port: u16,
+///    #[env = "DB_CONNECTION_HOST"]
+///    host: String,
+///    debug: Option<bool>,
+///}
+/// # std::env::set_var("DB_CONNECTION_PORT", "5432");
+/// # std::env::set_var("DB_CONNECTION_HOST", "localhost");
+/// let config = Config::new();
+/// println!("Port: {}", config.port);
+/// println!("Host: {}", config.host);
+/// // If DB_CONNECTION_PORT or DB_CONNECTION_HOST are not found in the environment, `new()` will panic.
+/// // If DEBUG is not found, it will be None.
+/// println!("Debug: {:?}", config.debug);
+/// ```
+/// The `env` attribute can be used to specify a different environment variable name.
+/// If the attribute is not present, the environment variable name will be the same as the field name in uppercase.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DB_CONNECTION_PORT"]
+///   port: u16,
+///   host: String,
+///}
+/// ```
+/// In this example, the environment variable for `port` will be `DB_CONNECTION_PORT` and the environment variable for `host` will be `HOST`.
+///
+/// The name can also be given with the other options as `env(name = "...")`,
+/// and a bare `#[env]` keeps the derived name.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(name = "NAME_EXAMPLE_PORT", default = "8080")]
+///   port: u16,
+///   #[env]
+///   name_example_host: String,
+///}
+/// # std::env::set_var("NAME_EXAMPLE_HOST", "localhost");
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.name_example_host, "localhost");
+/// ```
+///
+/// ## Aliases
+/// The `env(aliases = [...])` attribute lists fallback names that are tried in order
+/// when the primary variable isn't set, which keeps renamed variables working.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "ALIAS_EXAMPLE_DATABASE_URL"]
+///   #[env(aliases = ["ALIAS_EXAMPLE_OLD_DB_URL"])]
+///   database_url: String,
+///}
+/// # std::env::set_var("ALIAS_EXAMPLE_OLD_DB_URL", "postgres://localhost");
+/// let config = Config::new();
+/// assert_eq!(config.database_url, "postgres://localhost");
+/// ```
+///
+/// Listing the same alias twice is a compile error.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(aliases = ["OLD_DB_URL", "DB_URL", "OLD_DB_URL"])]
+///   database_url: String,
+///}
+/// ```
+///
+/// Marking the field with `env(deprecated = "...")` prints a warning with the given message
+/// whenever the value is read from one of the aliases, to nudge users towards the new name.
+/// Without aliases, the variable itself is deprecated and any use of it is reported.
+/// With the `log` feature the warning goes through `log::warn!`, otherwise to standard error.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEPRECATED_EXAMPLE_DATABASE_URL"]
+///   #[env(aliases = ["DEPRECATED_EXAMPLE_DB_URL"], deprecated = "use DEPRECATED_EXAMPLE_DATABASE_URL instead")]
+///   database_url: String,
+///}
+/// # std::env::set_var("DEPRECATED_EXAMPLE_DB_URL", "postgres://localhost/app");
+/// // Prints "warning: DEPRECATED_EXAMPLE_DB_URL is deprecated: use DEPRECATED_EXAMPLE_DATABASE_URL instead"
+/// let config = Config::new();
+/// assert_eq!(config.database_url, "postgres://localhost/app");
+/// ```
+///
+/// ## Prefixes
+/// The `envar(prefix = "...")` attribute on the struct prepends a prefix to every variable name,
+/// both the ones given with `env` and the ones derived from field names.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = "PREFIX_EXAMPLE_")]
+/// struct Config {
+///   port: u16,
+///   #[env = "HOSTNAME"]
+///   host: String,
+///}
+/// # std::env::set_var("PREFIX_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("PREFIX_EXAMPLE_HOSTNAME", "localhost");
+/// // Reads PREFIX_EXAMPLE_PORT and PREFIX_EXAMPLE_HOSTNAME
+/// let config = Config::new();
+/// # assert_eq!(config.port, 8080);
+/// # assert_eq!(config.host, "localhost");
+/// ```
+///
+/// When the prefix is only known at runtime, such as for several instances of a service in one
+/// process, `from_env_with_prefix()` puts it in front of every name, including any static prefix.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   port: u16,
+///}
+/// # std::env::set_var("RUNTIME_PREFIX_EXAMPLE_1_PORT", "8080");
+/// # std::env::set_var("RUNTIME_PREFIX_EXAMPLE_2_PORT", "8081");
+/// let first = Config::from_env_with_prefix("RUNTIME_PREFIX_EXAMPLE_1_").unwrap();
+/// let second = Config::from_env_with_prefix("RUNTIME_PREFIX_EXAMPLE_2_").unwrap();
+/// assert_eq!((first.port, second.port), (8080, 8081));
+///
+/// let err = Config::from_env_with_prefix("RUNTIME_PREFIX_EXAMPLE_3_").err().unwrap();
+/// assert_eq!(err.to_string(), "Environment variable RUNTIME_PREFIX_EXAMPLE_3_PORT not found");
+/// ```
+///
+/// ## Naming rules
+/// Without an explicit `env` name, a field is read from its name in uppercase, so `db_host`
+/// is read from `DB_HOST` but `dbHost` from `DBHOST`. The `envar(rename_all = "...")` attribute
+/// on the struct picks another rule: `UPPERCASE`, `lowercase`, `SCREAMING_SNAKE_CASE`,
+/// `snake_case`, `kebab-case` or `SCREAMING-KEBAB-CASE`. The case based rules split words on
+/// underscores and on lowercase to uppercase changes, so `dbHost` becomes `DB_HOST`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[allow(non_snake_case)]
+/// #[envar(prefix = "RENAME_EXAMPLE_", rename_all = "SCREAMING_SNAKE_CASE")]
+/// struct Config {
+///   dbHost: String,
+///   max_connections: u32,
+///}
+/// # std::env::set_var("RENAME_EXAMPLE_DB_HOST", "localhost");
+/// # std::env::set_var("RENAME_EXAMPLE_MAX_CONNECTIONS", "16");
+/// assert_eq!(Config::env_var_names(), ["RENAME_EXAMPLE_DB_HOST", "RENAME_EXAMPLE_MAX_CONNECTIONS"]);
+/// let config = Config::new();
+/// assert_eq!(config.dbHost, "localhost");
+/// ```
+///
+/// ## Nested structs
+/// A field whose type also derives `Envar` can be marked with `envar(flatten)`.
+/// It is then built with its own loader, reading the variables it declares from the same source.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct DbConfig {
+///   #[env = "FLATTEN_EXAMPLE_DB_HOST"]
+///   host: String,
+///}
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FLATTEN_EXAMPLE_PORT"]
+///   port: u16,
+///   #[envar(flatten)]
+///   database: DbConfig,
+///}
+/// # std::env::set_var("FLATTEN_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("FLATTEN_EXAMPLE_DB_HOST", "localhost");
+/// let config = Config::new();
+/// assert_eq!(config.database.host, "localhost");
+/// ```
+///
+/// `envar(flatten, prefix = "...")` reads the nested struct's variables with a prefix, after the
+/// outer struct's own, and a bare `prefix` derives it from the field name, so `database` reads
+/// `DATABASE_HOST`. Prefixes add up through every level of nesting, and the listings and errors
+/// use the full names.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Pool {
+///   #[default = "10"]
+///   size: u32,
+///}
+/// #[derive(Envar)]
+/// struct DbConfig {
+///   host: String,
+///   #[envar(flatten, prefix)]
+///   pool: Pool,
+///}
+/// #[derive(Envar)]
+/// #[envar(prefix = "NESTED_EXAMPLE_")]
+/// struct Config {
+///   #[envar(flatten, prefix)]
+///   database: DbConfig,
+///   #[envar(flatten, prefix = "REPLICA_")]
+///   replica: DbConfig,
+///}
+/// assert_eq!(Config::env_var_names(), [
+///     "NESTED_EXAMPLE_DATABASE_HOST",
+///     "NESTED_EXAMPLE_DATABASE_POOL_SIZE",
+///     "NESTED_EXAMPLE_REPLICA_HOST",
+///     "NESTED_EXAMPLE_REPLICA_POOL_SIZE",
+/// ]);
+/// # std::env::set_var("NESTED_EXAMPLE_DATABASE_HOST", "db.internal");
+/// # std::env::set_var("NESTED_EXAMPLE_DATABASE_POOL_SIZE", "20");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "Environment variable NESTED_EXAMPLE_REPLICA_HOST not found");
+/// # std::env::set_var("NESTED_EXAMPLE_REPLICA_HOST", "replica.internal");
+/// let config = Config::new();
+/// assert_eq!(config.database.pool.size, 20);
+/// assert_eq!(config.replica.host, "replica.internal");
+/// assert_eq!(config.replica.pool.size, 10);
+/// ```
+///
+/// A `Vec` of such structs is read with `envar(indexed_list = "COUNT")`: the variable `COUNT` holds
+/// the number of items, and item `i` is read with the prefix `COUNT_i_`, or `<item_prefix>i_` when
+/// `envar(item_prefix = "...")` is set too. Errors name the variables with the full prefix.
+/// A count above 1024 is rejected as invalid.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Backend {
+///   host: String,
+///   #[default = "80"]
+///   port: u16,
+///}
+/// #[derive(Envar)]
+/// #[envar(prefix = "INDEXED_EXAMPLE_")]
+/// struct Config {
+///   #[envar(indexed_list = "BACKENDS", item_prefix = "BACKEND_")]
+///   backends: Vec<Backend>,
+///}
+/// # std::env::set_var("INDEXED_EXAMPLE_BACKENDS", "2");
+/// # std::env::set_var("INDEXED_EXAMPLE_BACKEND_0_HOST", "a.internal");
+/// # std::env::set_var("INDEXED_EXAMPLE_BACKEND_1_HOST", "b.internal");
+/// # std::env::set_var("INDEXED_EXAMPLE_BACKEND_1_PORT", "8080");
+/// let config = Config::new();
+/// assert_eq!(config.backends.len(), 2);
+/// assert_eq!(config.backends[1].host, "b.internal");
+/// assert_eq!(config.backends[1].port, 8080);
+///
+/// std::env::set_var("INDEXED_EXAMPLE_BACKENDS", "3");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "Environment variable INDEXED_EXAMPLE_BACKEND_2_HOST not found");
+///
+/// std::env::set_var("INDEXED_EXAMPLE_BACKENDS", "18446744073709551615");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "INDEXED_EXAMPLE_BACKENDS must be at most 1024, got 18446744073709551615"
+/// );
+/// ```
+///
+/// ## `.env` files
+/// The `envar(dotenv = "...")` attribute on the struct reads a `.env` file alongside the environment.
+/// Each variable is resolved in this order, and the first one that has it wins:
+/// 1. the process environment,
+/// 2. the `.env` file,
+/// 3. the field's `default`.
+///
+/// A required field is only missing when all three come up empty.
+/// The file is never copied into the process environment, and a missing file is silently ignored.
+/// See `envar::dotenv::parse` for the accepted syntax.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(dotenv = "layered_example.env")]
+/// struct Config {
+///   #[env = "DOTENV_EXAMPLE_HOST"]
+///   host: String,
+///   #[env = "DOTENV_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env = "DOTENV_EXAMPLE_WORKERS"]
+///   #[default = "4"]
+///   workers: u16,
+///}
+/// # std::fs::write("layered_example.env", "DOTENV_EXAMPLE_HOST=localhost\nDOTENV_EXAMPLE_PORT=8080\n").unwrap();
+/// # std::env::set_var("DOTENV_EXAMPLE_PORT", "9090");
+/// let config = Config::new();
+/// # std::fs::remove_file("layered_example.env").unwrap();
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.port, 9090);
+/// assert_eq!(config.workers, 4);
+/// ```
+///
+/// ## Secrets
+/// The `envar(derive_debug)` attribute on the struct generates a `Debug` implementation.
+/// Fields marked with `env(secret)` are printed as `***` so they don't end up in logs.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(derive_debug)]
+/// struct Config {
+///   #[env = "SECRET_EXAMPLE_USER"]
+///   user: String,
+///   #[env = "SECRET_EXAMPLE_PASSWORD"]
+///   #[env(secret)]
+///   password: String,
+///}
+/// # std::env::set_var("SECRET_EXAMPLE_USER", "admin");
+/// # std::env::set_var("SECRET_EXAMPLE_PASSWORD", "hunter2");
+/// let config = Config::new();
+/// assert_eq!(format!("{:?}", config), r#"Config { user: "admin", password: *** }"#);
+/// ```
+///
+/// The `envar(display)` attribute generates a `Display` implementation summarizing the loaded
+/// config, one `NAME = value` line per variable, with `env(secret)` fields printed as `***`.
+/// Flattened structs need `envar(display)` too, and their lines are included in place.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(display)]
+/// struct Config {
+///   #[env = "DISPLAY_EXAMPLE_HOST"]
+///   host: String,
+///   #[env = "DISPLAY_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env = "DISPLAY_EXAMPLE_TOKEN"]
+///   #[env(secret)]
+///   token: String,
+///}
+/// # std::env::set_var("DISPLAY_EXAMPLE_HOST", "localhost");
+/// # std::env::set_var("DISPLAY_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("DISPLAY_EXAMPLE_TOKEN", "hunter2");
+/// let config = Config::new();
+/// assert_eq!(
+///     config.to_string(),
+///     "DISPLAY_EXAMPLE_HOST = \"localhost\"\nDISPLAY_EXAMPLE_PORT = 8080\nDISPLAY_EXAMPLE_TOKEN = ***\n"
+/// );
+/// ```
+///
+/// With `envar(as_map)` the struct gets an `as_map()` method returning the loaded config as a
+/// `HashMap<String, String>` keyed by variable name, for example to pass it on to a child process
+/// with `Command::envs`. Values are written in a form that reads back as the same value,
+/// unset optional fields are left out, and secrets are replaced with `***`.
+/// Flattened structs need `envar(as_map)` too, and `env(json)` fields must implement `serde::Serialize`.
+/// ```rust
+/// use envar::Envar;
+/// use std::time::Duration;
+/// #[derive(Envar)]
+/// #[envar(as_map)]
+/// struct Config {
+///   #[env = "AS_MAP_EXAMPLE_HOSTS"]
+///   hosts: Vec<String>,
+///   #[env = "AS_MAP_EXAMPLE_TIMEOUT"]
+///   timeout: Duration,
+///   #[env = "AS_MAP_EXAMPLE_WORKERS"]
+///   workers: Option<u16>,
+///   #[env = "AS_MAP_EXAMPLE_TOKEN"]
+///   #[env(secret)]
+///   token: String,
+///}
+/// # std::env::set_var("AS_MAP_EXAMPLE_HOSTS", "a.com, b.com");
+/// # std::env::set_var("AS_MAP_EXAMPLE_TIMEOUT", "90s");
+/// # std::env::set_var("AS_MAP_EXAMPLE_TOKEN", "hunter2");
+/// let map = Config::new().as_map();
+/// assert_eq!(map["AS_MAP_EXAMPLE_HOSTS"], "a.com,b.com");
+/// assert_eq!(map["AS_MAP_EXAMPLE_TIMEOUT"], "90s");
+/// assert_eq!(map["AS_MAP_EXAMPLE_TOKEN"], "***");
+/// assert!(!map.contains_key("AS_MAP_EXAMPLE_WORKERS"));
+/// ```
+///
+/// `env_diff()` compares the config with a fresh read of the environment, and returns the
+/// `(name, loaded, current)` values of every variable that changed since, sorted by name, with an
+/// unset variable as an empty string. A daemon can use it to decide whether to `reload()`.
+/// Values are compared as `as_map()` writes them, so changes to secrets go unnoticed. If the
+/// environment no longer loads, the variables that fail are reported with `"<unloadable>"` as
+/// their current value, and `try_new()` tells why.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(as_map)]
+/// struct Config {
+///   #[env = "ENV_DIFF_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env = "ENV_DIFF_EXAMPLE_HOST"]
+///   host: Option<String>,
+///}
+/// # std::env::set_var("ENV_DIFF_EXAMPLE_PORT", "8080");
+/// let config = Config::new();
+/// assert!(config.env_diff().is_empty());
+/// std::env::set_var("ENV_DIFF_EXAMPLE_PORT", "08080");
+/// assert!(config.env_diff().is_empty());
+/// std::env::set_var("ENV_DIFF_EXAMPLE_PORT", "9090");
+/// std::env::set_var("ENV_DIFF_EXAMPLE_HOST", "localhost");
+/// assert_eq!(config.env_diff(), [
+///     ("ENV_DIFF_EXAMPLE_HOST".to_string(), String::new(), "localhost".to_string()),
+///     ("ENV_DIFF_EXAMPLE_PORT".to_string(), "8080".to_string(), "9090".to_string()),
+/// ]);
+///
+/// std::env::set_var("ENV_DIFF_EXAMPLE_PORT", "http");
+/// assert_eq!(config.env_diff(), [
+///     ("ENV_DIFF_EXAMPLE_PORT".to_string(), "8080".to_string(), "<unloadable>".to_string()),
+/// ]);
+/// ```
+///
+/// To keep a value hidden wherever it goes, use the `envar::Secret<T>` type instead.
+/// It is parsed as a `T`, prints as `***` everywhere, and is wiped from memory on drop
+/// when the `zeroize` feature is enabled.
+///
+/// ## Trimming
+/// The `env(trim)` attribute removes surrounding whitespace from the value before it is parsed,
+/// and `envar(trim_all)` on the struct does the same for every field.
+/// A value that is blank after trimming is treated as unset.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "TRIM_EXAMPLE_PORT"]
+///   #[env(trim)]
+///   port: u16,
+///   #[env = "TRIM_EXAMPLE_HOST"]
+///   #[env(trim)]
+///   host: Option<String>,
+///}
+/// # std::env::set_var("TRIM_EXAMPLE_PORT", "8080\n");
+/// # std::env::set_var("TRIM_EXAMPLE_HOST", "   ");
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.host, None);
+/// ```
+///
+/// `env(trim_matches = "...")` strips any of the given characters from both ends of the value,
+/// such as the quotes left around a value by some exporters. Combined with `trim`,
+/// whitespace is removed first.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(name = "TRIM_MATCHES_EXAMPLE_NAME", trim, trim_matches = "\"'")]
+///   name: String,
+///}
+/// # std::env::set_var("TRIM_MATCHES_EXAMPLE_NAME", " \"my app\"\n");
+/// let config = Config::new();
+/// assert_eq!(config.name, "my app");
+/// ```
+///
+/// ## Empty values
+/// By default an empty value is passed on to the parser like any other.
+/// With `envar(empty_is_missing)` on the struct, an empty value is treated as unset instead:
+/// `Option` fields become `None`, defaults apply and required fields fail.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(empty_is_missing)]
+/// struct Config {
+///   #[env = "EMPTY_EXAMPLE_HOST"]
+///   #[default = "localhost"]
+///   host: String,
+///   #[env = "EMPTY_EXAMPLE_USER"]
+///   user: Option<String>,
+///}
+/// # std::env::set_var("EMPTY_EXAMPLE_HOST", "");
+/// # std::env::set_var("EMPTY_EXAMPLE_USER", "");
+/// let config = Config::new();
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.user, None);
+/// ```
+///
+/// `env(when_empty = "...")` decides for a single field, over the struct's setting: `"error"` rejects
+/// an empty value, `"default"` uses the field's default instead, and `"none"` makes an `Option` field `None`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "WHEN_EMPTY_EXAMPLE_TOKEN"]
+///   #[env(when_empty = "error")]
+///   token: Option<String>,
+///   #[env = "WHEN_EMPTY_EXAMPLE_REGION"]
+///   #[env(when_empty = "default", default = "eu-west-1")]
+///   region: String,
+///   #[env = "WHEN_EMPTY_EXAMPLE_WORKERS"]
+///   #[env(when_empty = "none")]
+///   workers: Option<u16>,
+///}
+/// # std::env::set_var("WHEN_EMPTY_EXAMPLE_TOKEN", "");
+/// # std::env::set_var("WHEN_EMPTY_EXAMPLE_REGION", "");
+/// # std::env::set_var("WHEN_EMPTY_EXAMPLE_WORKERS", "");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "WHEN_EMPTY_EXAMPLE_TOKEN must not be empty");
+/// # std::env::remove_var("WHEN_EMPTY_EXAMPLE_TOKEN");
+/// let config = Config::new();
+/// assert_eq!(config.token, None);
+/// assert_eq!(config.region, "eu-west-1");
+/// assert_eq!(config.workers, None);
+/// ```
+///
+/// ## Secret files
+/// Container platforms often mount secrets as files and point to them with a `<NAME>_FILE` variable.
+/// With `env(file_fallback)` on a field, or `envar(file_fallback)` on the struct, `<NAME>_FILE` is
+/// checked first and the trimmed contents of the file it names are used as the value.
+/// Otherwise `<NAME>` is read as usual. A `<NAME>_FILE` pointing at a missing file is an error.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FILE_EXAMPLE_DB_PASSWORD"]
+///   #[env(file_fallback)]
+///   db_password: String,
+///}
+/// # let path = std::env::temp_dir().join("envar_file_example_db_password");
+/// # std::fs::write(&path, "hunter2\n").unwrap();
+/// # std::env::set_var("FILE_EXAMPLE_DB_PASSWORD_FILE", &path);
+/// // FILE_EXAMPLE_DB_PASSWORD_FILE=/run/secrets/db_password
+/// let config = Config::new();
+/// assert_eq!(config.db_password, "hunter2");
+/// ```
+///
+/// `env(path_from = "...")` goes the other way: the named variable holds the path of a file, and the
+/// trimmed contents of the file are the field's value, as for PEM certificates. An unset variable is
+/// reported as missing, like any other, and a file that can't be read as `EnvarError::File`.
+/// ```rust
+/// use envar::{Envar, EnvarError};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(path_from = "PATH_FROM_EXAMPLE_CERT_PATH")]
+///   cert: String,
+///}
+/// let err = Config::try_new().err().unwrap();
+/// assert!(matches!(err, EnvarError::Missing { .. }));
+/// std::env::set_var("PATH_FROM_EXAMPLE_CERT_PATH", "/does/not/exist.pem");
+/// let err = Config::try_new().err().unwrap();
+/// assert!(matches!(err, EnvarError::File { .. }));
+/// # let path = std::env::temp_dir().join("envar_path_from_example_cert.pem");
+/// # std::fs::write(&path, "-----BEGIN CERTIFICATE-----\n").unwrap();
+/// # std::env::set_var("PATH_FROM_EXAMPLE_CERT_PATH", &path);
+/// let config = Config::new();
+/// assert_eq!(config.cert, "-----BEGIN CERTIFICATE-----");
+/// ```
+///
+/// ## Interpolation
+/// With `env(expand)` on a field, or `envar(expand)` on the struct, `${OTHER}` references in a value
+/// are replaced with the value of `OTHER`. `${OTHER:-default}` falls back to `default` when `OTHER`
+/// is unset, and `$$` stands for a literal `$`. Referencing an unset variable without a default is an error.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "EXPAND_EXAMPLE_URL"]
+///   #[env(expand)]
+///   url: String,
+///}
+/// # std::env::set_var("EXPAND_EXAMPLE_HOST", "example.com");
+/// # std::env::set_var("EXPAND_EXAMPLE_URL", "https://${EXPAND_EXAMPLE_HOST}:${EXPAND_EXAMPLE_PORT:-443}/api");
+/// let config = Config::new();
+/// assert_eq!(config.url, "https://example.com:443/api");
+/// ```
+///
+/// ## Default values
+/// The `default` attribute provides a fallback value for when the environment variable is not set.
+/// The default is parsed just like the variable would be, so it must be valid for the field type.
+/// It can also be written as `env(default = "...")`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEFAULT_EXAMPLE_PORT"]
+///   #[default = "8080"]
+///   port: u16,
+///}
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// ```
+///
+/// For defaults that have to be computed, `env(default_fn = "...")` names a `fn() -> T`
+/// that is called when the variable isn't set. Its result is used as is, without parsing.
+/// A field can't have both a `default` and a `default_fn`.
+/// ```rust
+/// use envar::Envar;
+/// fn default_workers() -> usize {
+///     std::thread::available_parallelism().map_or(1, |n| n.get())
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEFAULT_FN_EXAMPLE_WORKERS"]
+///   #[env(default_fn = "default_workers")]
+///   workers: usize,
+///}
+/// let config = Config::new();
+/// assert_eq!(config.workers, default_workers());
+/// ```
+/// ```compile_fail
+/// use envar::Envar;
+/// fn default_port() -> u16 { 8080 }
+/// #[derive(Envar)]
+/// struct Config {
+///   #[default = "80"]
+///   #[env(default_fn = "default_port")]
+///   port: u16,
+///}
+/// ```
+///
+/// A default that is already a typed constant is given with `env(default_const = ...)`, without quotes,
+/// and is used as is as well. A field has at most one of `default`, `default_fn` and `default_const`.
+/// ```rust
+/// use envar::Envar;
+/// const DEFAULT_PORT: u16 = 8080;
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEFAULT_CONST_EXAMPLE_PORT"]
+///   #[env(default_const = DEFAULT_PORT)]
+///   port: u16,
+///}
+/// assert_eq!(Config::new().port, DEFAULT_PORT);
+/// ```
+///
+/// A default that depends on other fields is given with `env(default_expr = "...")`. Fields are read
+/// from top to bottom, and the expression can refer to any field declared before its own, as a reference
+/// to the value that field was given. A failure in one of those fields is reported on its own,
+/// without evaluating the expression.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEFAULT_EXPR_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env(name = "DEFAULT_EXPR_EXAMPLE_METRICS_PORT", default_expr = "port + 1")]
+///   metrics_port: u16,
+///   #[env(name = "DEFAULT_EXPR_EXAMPLE_URL", default_expr = "format!(\"http://localhost:{}\", port)")]
+///   url: String,
+///}
+/// # std::env::set_var("DEFAULT_EXPR_EXAMPLE_PORT", "8080");
+/// let config = Config::new();
+/// assert_eq!(config.metrics_port, 8081);
+/// assert_eq!(config.url, "http://localhost:8080");
+/// ```
+/// A field declared later isn't read yet, so it can't be used:
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(default_expr = "port + 1")]
+///   metrics_port: u16,
+///   port: u16,
+///}
+/// ```
+///
+/// The `envar(derive_default)` attribute on the struct generates a `Default` implementation
+/// built from those defaults without reading the environment at all, with `Option` fields
+/// left as `None` when they have no default. Every other field must have a default.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(derive_default)]
+/// struct Config {
+///   #[env = "DERIVE_DEFAULT_EXAMPLE_PORT"]
+///   #[default = "8080"]
+///   port: u16,
+///   #[env = "DERIVE_DEFAULT_EXAMPLE_HOST"]
+///   host: Option<String>,
+///}
+/// # std::env::set_var("DERIVE_DEFAULT_EXAMPLE_PORT", "9090");
+/// let config = Config::default();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.host, None);
+/// ```
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(derive_default)]
+/// struct Config {
+///   port: u16,
+///}
+/// ```
+///
+/// ## Floats
+/// `f32` and `f64` fields reject `NaN` and infinite values, which `FromStr` would accept.
+/// `env(allow_nonfinite)` lets them through.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FLOAT_EXAMPLE_RATE"]
+///   rate: f64,
+///   #[env = "FLOAT_EXAMPLE_CEILING"]
+///   #[env(allow_nonfinite)]
+///   ceiling: f32,
+///}
+/// # std::env::set_var("FLOAT_EXAMPLE_RATE", "nan");
+/// # std::env::set_var("FLOAT_EXAMPLE_CEILING", "inf");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "FLOAT_EXAMPLE_RATE must be a finite number, got NaN");
+/// # std::env::set_var("FLOAT_EXAMPLE_RATE", "0.25");
+/// let config = Config::new();
+/// assert_eq!(config.rate, 0.25);
+/// assert!(config.ceiling.is_infinite());
+/// ```
+///
+/// ## Percentages
+/// `env(percent)` reads an `f32` or `f64` from either a ratio like `0.1` or a percentage like `10%`,
+/// which is divided by 100. The result must be between 0 and 1, unless the field sets its own
+/// `min` and `max`, which compare against the ratio.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PERCENT_EXAMPLE_SAMPLE_RATE"]
+///   #[env(percent)]
+///   sample_rate: f64,
+///   #[env = "PERCENT_EXAMPLE_ERROR_BUDGET"]
+///   #[env(percent)]
+///   error_budget: f64,
+///   #[env = "PERCENT_EXAMPLE_SCALE"]
+///   #[env(percent, max = 2.0)]
+///   scale: f32,
+///}
+/// # std::env::set_var("PERCENT_EXAMPLE_SAMPLE_RATE", "10%");
+/// # std::env::set_var("PERCENT_EXAMPLE_ERROR_BUDGET", "150%");
+/// # std::env::set_var("PERCENT_EXAMPLE_SCALE", "150%");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "PERCENT_EXAMPLE_ERROR_BUDGET must be between 0 and 1, got 1.5");
+/// # std::env::set_var("PERCENT_EXAMPLE_ERROR_BUDGET", "0.001");
+/// let config = Config::new();
+/// assert_eq!(config.sample_rate, 0.1);
+/// assert_eq!(config.error_budget, 0.001);
+/// assert_eq!(config.scale, 1.5);
+/// ```
+///
+/// ## Integer bases
+/// `env(radix = 16)` reads an integer written in another base: 2, 8, 10 or 16.
+/// The matching `0b`, `0o` or `0x` prefix is optional.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "RADIX_EXAMPLE_PERMISSIONS"]
+///   #[env(radix = 16)]
+///   permissions: u32,
+///   #[env = "RADIX_EXAMPLE_MODE"]
+///   #[env(radix = 8)]
+///   mode: Option<u16>,
+///}
+/// # std::env::set_var("RADIX_EXAMPLE_PERMISSIONS", "0x1F");
+/// # std::env::set_var("RADIX_EXAMPLE_MODE", "0o9");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable RADIX_EXAMPLE_MODE=\"0o9\": \
+///      not a valid base 8 integer (invalid digit found in string)"
+/// );
+/// # std::env::set_var("RADIX_EXAMPLE_MODE", "755");
+/// let config = Config::new();
+/// assert_eq!(config.permissions, 0x1F);
+/// assert_eq!(config.mode, Some(0o755));
+/// ```
+///
+/// ## Counts
+/// `env(count)` reads a level for verbosity style settings, given either as a number or as a
+/// repeated character, so `VERBOSITY=vvv` and `VERBOSITY=3` are both `3`.
+/// A count that doesn't fit in the field's integer type is an error.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "COUNT_EXAMPLE_VERBOSITY"]
+///   #[env(count)]
+///   verbosity: u8,
+///}
+/// # std::env::set_var("COUNT_EXAMPLE_VERBOSITY", "vvv");
+/// assert_eq!(Config::new().verbosity, 3);
+/// # std::env::set_var("COUNT_EXAMPLE_VERBOSITY", "2");
+/// assert_eq!(Config::new().verbosity, 2);
+/// # std::env::set_var("COUNT_EXAMPLE_VERBOSITY", "300");
+/// assert!(Config::try_new().is_err());
+/// ```
+///
+/// ## Byte sizes
+/// `env(bytesize)` reads an integer field as a number of bytes with an optional unit:
+/// `K`, `M`, `G` and `T` are powers of 1000, `Ki`, `Mi`, `Gi` and `Ti` are powers of 1024,
+/// and either may be followed by `B`. A size too large for the field is an error.
+/// `env(size)` is accepted as another spelling of `env(bytesize)`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "BYTESIZE_EXAMPLE_MAX_UPLOAD"]
+///   #[env(bytesize)]
+///   max_upload: usize,
+///   #[env = "BYTESIZE_EXAMPLE_BUFFER"]
+///   #[env(size)]
+///   buffer: u16,
+///}
+/// # std::env::set_var("BYTESIZE_EXAMPLE_MAX_UPLOAD", "10MB");
+/// # std::env::set_var("BYTESIZE_EXAMPLE_BUFFER", "1MiB");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable BYTESIZE_EXAMPLE_BUFFER=\"1MiB\": \
+///      1048576 bytes don't fit in u16"
+/// );
+/// # std::env::set_var("BYTESIZE_EXAMPLE_BUFFER", "4KiB");
+/// let config = Config::new();
+/// assert_eq!(config.max_upload, 10_000_000);
+/// assert_eq!(config.buffer, 4096);
+/// ```
+///
+/// `env(default_unit = "...")` sets the unit of a bare number, as Redis and nginx do,
+/// while a value with its own unit keeps it.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DEFAULT_UNIT_EXAMPLE_MAX_CACHE"]
+///   #[env(bytesize, default_unit = "MiB")]
+///   max_cache: u64,
+///   #[env = "DEFAULT_UNIT_EXAMPLE_MAX_LOG"]
+///   #[env(bytesize, default_unit = "MiB")]
+///   max_log: u64,
+///}
+/// # std::env::set_var("DEFAULT_UNIT_EXAMPLE_MAX_CACHE", "512");
+/// # std::env::set_var("DEFAULT_UNIT_EXAMPLE_MAX_LOG", "1GiB");
+/// let config = Config::new();
+/// assert_eq!(config.max_cache, 512 * 1024 * 1024);
+/// assert_eq!(config.max_log, 1024 * 1024 * 1024);
+/// ```
+///
+/// ## Booleans
+/// `bool` fields accept the usual shell spellings, ignoring case:
+/// `true`, `1`, `yes` and `on` are `true`, while `false`, `0`, `no` and `off` are `false`.
+/// Anything else is a parse error.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "BOOL_EXAMPLE_DEBUG"]
+///   debug: bool,
+///   #[env = "BOOL_EXAMPLE_VERBOSE"]
+///   verbose: Option<bool>,
+///}
+/// # std::env::set_var("BOOL_EXAMPLE_DEBUG", "1");
+/// # std::env::set_var("BOOL_EXAMPLE_VERBOSE", "Off");
+/// let config = Config::new();
+/// assert!(config.debug);
+/// assert_eq!(config.verbose, Some(false));
+/// ```
+///
+/// With `env(flag)`, a `bool` is `true` whenever the variable is set, whatever its value,
+/// even an empty one, and `false` when it isn't.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FLAG_EXAMPLE_VERBOSE"]
+///   #[env(flag)]
+///   verbose: bool,
+///   #[env = "FLAG_EXAMPLE_QUIET"]
+///   #[env(flag)]
+///   quiet: bool,
+///}
+/// # std::env::set_var("FLAG_EXAMPLE_VERBOSE", "");
+/// let config = Config::new();
+/// assert!(config.verbose);
+/// assert!(!config.quiet);
+/// ```
+///
+/// ## Durations
+/// `std::time::Duration` fields are read from human readable values such as `10ms`, `30s`, `5m` or `1h`.
+/// Units can be combined, as in `1h30m`, and the accepted units are `ns`, `us`, `ms`, `s`, `m`, `h` and `d`.
+/// ```rust
+/// use envar::Envar;
+/// use std::time::Duration;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DURATION_EXAMPLE_TIMEOUT"]
+///   timeout: Duration,
+///   #[env = "DURATION_EXAMPLE_IDLE"]
+///   idle: Option<Duration>,
+///}
+/// # std::env::set_var("DURATION_EXAMPLE_TIMEOUT", "1m30s");
+/// # std::env::set_var("DURATION_EXAMPLE_IDLE", "250ms");
+/// let config = Config::new();
+/// assert_eq!(config.timeout, Duration::from_secs(90));
+/// assert_eq!(config.idle, Some(Duration::from_millis(250)));
+/// ```
+///
+/// `env(secs)` and `env(millis)` read the value as a plain whole number of seconds or milliseconds instead.
+/// ```rust
+/// use envar::Envar;
+/// use std::time::Duration;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DURATION_EXAMPLE_TIMEOUT_SECS"]
+///   #[env(secs)]
+///   timeout: Duration,
+///   #[env = "DURATION_EXAMPLE_POLL_MILLIS"]
+///   #[env(millis)]
+///   poll_interval: Duration,
+///}
+/// # std::env::set_var("DURATION_EXAMPLE_TIMEOUT_SECS", "30");
+/// # std::env::set_var("DURATION_EXAMPLE_POLL_MILLIS", "250");
+/// let config = Config::new();
+/// assert_eq!(config.timeout, Duration::from_secs(30));
+/// assert_eq!(config.poll_interval, Duration::from_millis(250));
+/// ```
+///
+/// ## Dates and times
+/// With the `chrono` feature enabled, chrono's `DateTime`, `NaiveDate`, `NaiveTime` and
+/// `NaiveDateTime` fields are supported. `env(format = "...")` sets the format to parse with,
+/// which otherwise is RFC 3339 for `DateTime` and ISO 8601 for the naive types.
+/// A value that doesn't match is reported along with the expected format.
+/// ```rust
+/// use chrono::{DateTime, NaiveDate, Utc};
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "CHRONO_EXAMPLE_RELEASE_DATE"]
+///   #[env(format = "%d/%m/%Y")]
+///   release_date: NaiveDate,
+///   #[env = "CHRONO_EXAMPLE_MAINTENANCE_START"]
+///   maintenance_start: Option<DateTime<Utc>>,
+///}
+/// # std::env::set_var("CHRONO_EXAMPLE_RELEASE_DATE", "2024-03-01");
+/// let err = Config::try_new().err().unwrap();
+/// assert!(err.to_string().contains("=\"2024-03-01\": expected the format %d/%m/%Y"));
+/// # std::env::set_var("CHRONO_EXAMPLE_RELEASE_DATE", "01/03/2024");
+/// # std::env::set_var("CHRONO_EXAMPLE_MAINTENANCE_START", "2024-03-02T01:00:00+02:00");
+/// let config = Config::new();
+/// assert_eq!(config.release_date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+/// assert_eq!(config.maintenance_start.unwrap().to_rfc3339(), "2024-03-01T23:00:00+00:00");
+/// ```
+///
+/// The `time` feature does the same for `time::OffsetDateTime` and the `jiff` feature for
+/// `jiff::Timestamp`, both parsed from RFC 3339 by default. Formats are written in the syntax of
+/// the crate they belong to, `[year]-[month]-[day]` for `time` and `%Y-%m-%d` for `jiff`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "TIME_EXAMPLE_STARTED_AT"]
+///   started_at: time::OffsetDateTime,
+///   #[env = "TIME_EXAMPLE_EXPIRES_AT"]
+///   #[env(format = "[day]/[month]/[year] [hour]:[minute] [offset_hour]")]
+///   expires_at: time::OffsetDateTime,
+///   #[env = "JIFF_EXAMPLE_DEPLOYED_AT"]
+///   deployed_at: jiff::Timestamp,
+///   #[env = "JIFF_EXAMPLE_RETIRED_AT"]
+///   #[env(format = "%d/%m/%Y %H:%M %z")]
+///   retired_at: Option<jiff::Timestamp>,
+///}
+/// # std::env::set_var("TIME_EXAMPLE_STARTED_AT", "2024-03-01T12:00:00Z");
+/// # std::env::set_var("TIME_EXAMPLE_EXPIRES_AT", "01/04/2024 12:00 +02");
+/// # std::env::set_var("JIFF_EXAMPLE_DEPLOYED_AT", "2024-03-01T12:00:00Z");
+/// # std::env::set_var("JIFF_EXAMPLE_RETIRED_AT", "2024-03-01");
+/// let err = Config::try_new().err().unwrap();
+/// assert!(err.to_string().contains("=\"2024-03-01\": expected a timestamp in the format %d/%m/%Y %H:%M %z"));
+/// # std::env::set_var("JIFF_EXAMPLE_RETIRED_AT", "01/04/2024 12:00 +0200");
+/// let config = Config::new();
+/// assert_eq!(config.started_at, time::macros::datetime!(2024-03-01 12:00 UTC));
+/// assert_eq!(config.expires_at, time::macros::datetime!(2024-04-01 10:00 UTC));
+/// assert_eq!(config.deployed_at.to_string(), "2024-03-01T12:00:00Z");
+/// assert_eq!(config.retired_at.unwrap().to_string(), "2024-04-01T10:00:00Z");
+/// ```
+///
+/// ## JSON values
+/// With the `serde` feature enabled, `env(json)` deserializes the value as JSON into any type
+/// implementing `serde::Deserialize`, for structured config that doesn't fit a plain string.
+/// ```rust
+/// use envar::Envar;
+/// use serde::Deserialize;
+/// use std::collections::HashMap;
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Limits {
+///     requests: u32,
+///     burst: u32,
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "JSON_EXAMPLE_ROUTES"]
+///   #[env(json)]
+///   routes: HashMap<String, u16>,
+///   #[env = "JSON_EXAMPLE_LIMITS"]
+///   #[env(json)]
+///   limits: Option<Limits>,
+///}
+/// # std::env::set_var("JSON_EXAMPLE_ROUTES", r#"{"a": 1, "b": 2}"#);
+/// # std::env::set_var("JSON_EXAMPLE_LIMITS", r#"{"requests": 100}"#);
+/// let err = Config::try_new().err().unwrap();
+/// assert!(err.to_string().starts_with(
+///     r#"Failed to parse environment variable JSON_EXAMPLE_LIMITS="{\"requests\": 100}": missing field `burst`"#
+/// ));
+/// # std::env::set_var("JSON_EXAMPLE_LIMITS", r#"{"requests": 100, "burst": 10}"#);
+/// let config = Config::new();
+/// assert_eq!(config.routes["b"], 2);
+/// assert_eq!(config.limits, Some(Limits { requests: 100, burst: 10 }));
+/// ```
+///
+/// With `envar(serde)` on a struct that derives `serde::Deserialize`, the raw values are handed to
+/// serde instead, so serde's own attributes such as `serde(default)` and `serde(deserialize_with)`
+/// apply. Variables are still named by envar and looked up with its value handling options, while
+/// all parsing is left to serde: numbers, booleans, enums by variant name and comma separated lists
+/// are supported. Fields are matched by their Rust names, so `serde(rename)` doesn't apply.
+/// ```rust
+/// use envar::Envar;
+/// use serde::Deserialize;
+/// #[derive(Envar, Deserialize)]
+/// #[envar(serde, prefix = "SERDE_EXAMPLE_")]
+/// struct Config {
+///   port: u16,
+///   #[serde(default)]
+///   debug: bool,
+///   #[env(trim)]
+///   hosts: Vec<String>,
+///   level: Option<Level>,
+///}
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Level {
+///   Info,
+///   Debug,
+///}
+/// # std::env::set_var("SERDE_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("SERDE_EXAMPLE_HOSTS", " a.com,b.com ");
+/// # std::env::set_var("SERDE_EXAMPLE_LEVEL", "debug");
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// assert!(!config.debug);
+/// assert_eq!(config.hosts, ["a.com", "b.com"]);
+/// assert_eq!(config.level, Some(Level::Debug));
+///
+/// std::env::set_var("SERDE_EXAMPLE_PORT", "http");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable SERDE_EXAMPLE_PORT=\"http\": expected a non-negative integer"
+/// );
+/// std::env::remove_var("SERDE_EXAMPLE_PORT");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "Environment variable SERDE_EXAMPLE_PORT not found");
+/// ```
+///
+/// ## Binary values
+/// With the `base64` feature enabled, `env(base64)` decodes a standard base64 value
+/// into a `Vec<u8>` field.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "BASE64_EXAMPLE_SIGNING_KEY"]
+///   #[env(base64)]
+///   signing_key: Vec<u8>,
+///   #[env = "BASE64_EXAMPLE_PREVIOUS_KEY"]
+///   #[env(base64)]
+///   previous_key: Option<Vec<u8>>,
+///}
+/// # std::env::set_var("BASE64_EXAMPLE_SIGNING_KEY", "c2VjcmV0");
+/// # std::env::set_var("BASE64_EXAMPLE_PREVIOUS_KEY", "not base64!");
+/// let err = Config::try_new().err().unwrap();
+/// assert!(err.to_string().starts_with(
+///     "Failed to parse environment variable BASE64_EXAMPLE_PREVIOUS_KEY=\"not base64!\": not valid base64"
+/// ));
+/// # std::env::remove_var("BASE64_EXAMPLE_PREVIOUS_KEY");
+/// let config = Config::new();
+/// assert_eq!(config.signing_key, b"secret");
+/// assert_eq!(config.previous_key, None);
+/// ```
+///
+/// ## URLs
+/// With the `url` feature enabled, `url::Url` fields are read with `Url::parse`,
+/// and a malformed URL is reported along with the reason it was rejected.
+/// ```rust
+/// use envar::Envar;
+/// use url::Url;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "URL_EXAMPLE_DATABASE_URL"]
+///   database_url: Url,
+///   #[env = "URL_EXAMPLE_PROXY"]
+///   proxy: Option<Url>,
+///}
+/// # std::env::set_var("URL_EXAMPLE_DATABASE_URL", "postgres://localhost:5432/app");
+/// # std::env::set_var("URL_EXAMPLE_PROXY", "not a url");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable URL_EXAMPLE_PROXY=\"not a url\": \
+///      not a valid URL (relative URL without a base)"
+/// );
+/// # std::env::remove_var("URL_EXAMPLE_PROXY");
+/// let config = Config::new();
+/// assert_eq!(config.database_url.port(), Some(5432));
+/// ```
+///
+/// ## Strings
+/// Besides `String`, string fields can be `Box<str>` or `Cow<'static, str>`.
+/// ```rust
+/// use envar::Envar;
+/// use std::borrow::Cow;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "STRING_EXAMPLE_NAME"]
+///   name: Box<str>,
+///   #[env = "STRING_EXAMPLE_REGION"]
+///   #[default = "eu-west-1"]
+///   region: Cow<'static, str>,
+///   #[env = "STRING_EXAMPLE_ZONE"]
+///   zone: Option<Box<str>>,
+///}
+/// # std::env::set_var("STRING_EXAMPLE_NAME", "api");
+/// let config = Config::new();
+/// assert_eq!(&*config.name, "api");
+/// assert_eq!(config.region, "eu-west-1");
+/// assert_eq!(config.zone, None);
+/// ```
+///
+/// ## Paths
+/// `PathBuf` and `OsString` fields are read with `std::env::var_os`, so values that aren't valid UTF-8
+/// are kept intact. Processing the value, with `trim`, `expand` or `transform` for instance, needs it
+/// to be valid UTF-8 though. Any other field reports a value that isn't valid UTF-8 as
+/// `EnvarError::NotUnicode`, rather than pretending the variable isn't set.
+/// ```rust
+/// use envar::Envar;
+/// use std::ffi::OsString;
+/// use std::path::PathBuf;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PATH_EXAMPLE_CONFIG_DIR"]
+///   config_dir: PathBuf,
+///   #[env = "PATH_EXAMPLE_CACHE_DIR"]
+///   cache_dir: Option<OsString>,
+///}
+/// # std::env::set_var("PATH_EXAMPLE_CONFIG_DIR", "/etc/app");
+/// # std::env::set_var("PATH_EXAMPLE_CACHE_DIR", "/var/cache/app");
+/// let config = Config::new();
+/// assert_eq!(config.config_dir, PathBuf::from("/etc/app"));
+/// assert_eq!(config.cache_dir, Some(OsString::from("/var/cache/app")));
+/// ```
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// use envar::{Envar, EnvarError};
+/// use std::os::unix::ffi::OsStrExt;
+/// use std::path::PathBuf;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "NOT_UNICODE_EXAMPLE_DATA_DIR"]
+///   data_dir: PathBuf,
+///   #[env = "NOT_UNICODE_EXAMPLE_NAME"]
+///   name: Option<String>,
+///}
+/// let value = std::ffi::OsStr::from_bytes(b"/srv/caf\xe9");
+/// std::env::set_var("NOT_UNICODE_EXAMPLE_DATA_DIR", value);
+/// std::env::set_var("NOT_UNICODE_EXAMPLE_NAME", value);
+/// let err = Config::try_new().err().unwrap();
+/// assert!(matches!(err, EnvarError::NotUnicode { ref var, .. } if var == "NOT_UNICODE_EXAMPLE_NAME"));
+/// std::env::remove_var("NOT_UNICODE_EXAMPLE_NAME");
+/// assert_eq!(Config::new().data_dir.as_os_str(), value);
+/// # }
+/// ```
+///
+/// `env(must_exist)` checks at startup that the path exists, and `must_exist = "dir"` or
+/// `must_exist = "file"` that it is a directory or a regular file.
+/// It works for any type that implements `AsRef<Path>`, such as `PathBuf` and `String`.
+/// ```rust
+/// use envar::Envar;
+/// use std::path::PathBuf;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "MUST_EXIST_EXAMPLE_DATA_DIR"]
+///   #[env(must_exist = "dir")]
+///   data_dir: PathBuf,
+///}
+/// # let dir = std::env::temp_dir();
+/// # std::env::set_var("MUST_EXIST_EXAMPLE_DATA_DIR", &dir);
+/// assert_eq!(Config::new().data_dir, dir);
+/// # std::env::set_var("MUST_EXIST_EXAMPLE_DATA_DIR", "/does/not/exist");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "MUST_EXIST_EXAMPLE_DATA_DIR points to /does/not/exist, which does not exist"
+/// );
+/// ```
+///
+/// ## Validation
+/// The `env(min = ..., max = ...)` attributes reject numbers outside a range, using the field's own type.
+/// Either bound can be given on its own.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "RANGE_EXAMPLE_WORKERS"]
+///   #[env(min = 1, max = 64)]
+///   workers: usize,
+///}
+/// # std::env::set_var("RANGE_EXAMPLE_WORKERS", "0");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "RANGE_EXAMPLE_WORKERS must be between 1 and 64, got 0");
+/// ```
+///
+/// Similarly, `env(min_len = ..., max_len = ...)` checks the length of string values in characters.
+/// For `Option` fields the check only runs when the variable is set.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LEN_EXAMPLE_API_KEY"]
+///   #[env(min_len = 32, max_len = 32)]
+///   api_key: Option<String>,
+///}
+/// # std::env::set_var("LEN_EXAMPLE_API_KEY", "truncated");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "LEN_EXAMPLE_API_KEY must be between 32 and 32 characters long, got 9");
+/// ```
+///
+/// With the `regex` feature enabled, `env(pattern = "...")` checks string values against a regex.
+/// The regex is checked at compile time, and matches anywhere in the value unless anchored.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PATTERN_EXAMPLE_EMAIL_FROM"]
+///   #[env(pattern = r"^[^@\s]+@[^@\s]+$")]
+///   email_from: String,
+///}
+/// # std::env::set_var("PATTERN_EXAMPLE_EMAIL_FROM", "not an email");
+/// assert!(Config::try_new().is_err());
+/// ```
+/// An invalid regex is reported at build time.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(pattern = "[unclosed")]
+///   email_from: String,
+///}
+/// ```
+///
+/// Other rules go in a function given with `env(validate = "...")`, which receives the parsed value
+/// after the built-in checks and returns why it is invalid, if it is.
+/// ```rust
+/// use envar::Envar;
+/// fn aws_region(region: &String) -> Result<(), String> {
+///     if ["us-east-1", "eu-west-1"].contains(&region.as_str()) {
+///         Ok(())
+///     } else {
+///         Err(format!("must be a known AWS region, got '{}'", region))
+///     }
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "VALIDATE_EXAMPLE_REGION"]
+///   #[env(validate = "aws_region")]
+///   region: String,
+///}
+/// # std::env::set_var("VALIDATE_EXAMPLE_REGION", "mars-north-1");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "VALIDATE_EXAMPLE_REGION must be a known AWS region, got 'mars-north-1'");
+/// # std::env::set_var("VALIDATE_EXAMPLE_REGION", "eu-west-1");
+/// assert_eq!(Config::new().region, "eu-west-1");
+/// ```
+///
+/// ## Non-zero integers
+/// The `std::num::NonZero*` types reject zero with an error naming the expected type,
+/// such as `PORT must be a non-zero u16, got '0'`.
+/// ```rust
+/// use envar::Envar;
+/// use std::num::{NonZeroU16, NonZeroU32};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "NON_ZERO_EXAMPLE_WORKERS"]
+///   workers: NonZeroU16,
+///   #[env = "NON_ZERO_EXAMPLE_LIMIT"]
+///   limit: Option<NonZeroU32>,
+///}
+/// # std::env::set_var("NON_ZERO_EXAMPLE_WORKERS", "4");
+/// # std::env::set_var("NON_ZERO_EXAMPLE_LIMIT", "0");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "NON_ZERO_EXAMPLE_LIMIT must be a non-zero u32, got '0'");
+/// # std::env::remove_var("NON_ZERO_EXAMPLE_LIMIT");
+/// let config = Config::new();
+/// assert_eq!(config.workers.get(), 4);
+/// assert_eq!(config.limit, None);
+/// ```
+///
+/// ## Network addresses
+/// `SocketAddr`, `IpAddr`, `Ipv4Addr`, `Ipv6Addr` and their variants from `std::net` are supported,
+/// with errors that say which kind of address was expected.
+/// ```rust
+/// use envar::Envar;
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "NET_EXAMPLE_BIND_ADDR"]
+///   bind_addr: SocketAddr,
+///   #[env = "NET_EXAMPLE_METRICS_ADDR"]
+///   metrics_addr: Option<SocketAddr>,
+///   #[env = "NET_EXAMPLE_PEER"]
+///   peer: IpAddr,
+///   #[env = "NET_EXAMPLE_GATEWAY"]
+///   gateway: Ipv4Addr,
+///   #[env = "NET_EXAMPLE_DNS"]
+///   dns: Ipv6Addr,
+///}
+/// # std::env::set_var("NET_EXAMPLE_BIND_ADDR", "0.0.0.0:8080");
+/// # std::env::set_var("NET_EXAMPLE_PEER", "::1");
+/// # std::env::set_var("NET_EXAMPLE_GATEWAY", "10.0.0.1");
+/// # std::env::set_var("NET_EXAMPLE_DNS", "2001:4860:4860::8888");
+/// let config = Config::new();
+/// assert_eq!(config.bind_addr, "0.0.0.0:8080".parse().unwrap());
+/// assert_eq!(config.metrics_addr, None);
+/// assert_eq!(config.peer, IpAddr::V6(Ipv6Addr::LOCALHOST));
+/// assert_eq!(config.gateway, Ipv4Addr::new(10, 0, 0, 1));
+/// assert_eq!(config.dns, "2001:4860:4860::8888".parse::<Ipv6Addr>().unwrap());
+///
+/// std::env::set_var("NET_EXAMPLE_METRICS_ADDR", "0.0.0.0");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable NET_EXAMPLE_METRICS_ADDR=\"0.0.0.0\": not a valid socket address",
+/// );
+/// ```
+///
+/// ## Lists
+/// `Vec<T>` fields are read from a comma separated value, with each element parsed into `T`.
+/// Whitespace around elements is trimmed, and an empty value gives an empty `Vec`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LIST_EXAMPLE_ORIGINS"]
+///   origins: Vec<String>,
+///   #[env = "LIST_EXAMPLE_PORTS"]
+///   ports: Vec<u16>,
+///}
+/// # std::env::set_var("LIST_EXAMPLE_ORIGINS", "a.com, b.com,c.com");
+/// # std::env::set_var("LIST_EXAMPLE_PORTS", "");
+/// let config = Config::new();
+/// assert_eq!(config.origins, vec!["a.com", "b.com", "c.com"]);
+/// assert!(config.ports.is_empty());
+/// ```
+/// The `env(delimiter = "...")` attribute splits on a different separator instead.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "DELIMITER_EXAMPLE_QUERIES"]
+///   #[env(delimiter = ";")]
+///   queries: Vec<String>,
+///}
+/// # std::env::set_var("DELIMITER_EXAMPLE_QUERIES", "a=1,b=2;c=3");
+/// let config = Config::new();
+/// assert_eq!(config.queries, vec!["a=1,b=2", "c=3"]);
+/// ```
+/// `env(split_whitespace)` splits on any run of whitespace instead, for lists such as
+/// `NO_PROXY=a.com b.com`. A field can't have both a `delimiter` and `split_whitespace`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SPLIT_WHITESPACE_EXAMPLE_NO_PROXY"]
+///   #[env(split_whitespace)]
+///   no_proxy: Vec<String>,
+///}
+/// # std::env::set_var("SPLIT_WHITESPACE_EXAMPLE_NO_PROXY", " a.com  b.com\tc.com ");
+/// let config = Config::new();
+/// assert_eq!(config.no_proxy, vec!["a.com", "b.com", "c.com"]);
+/// ```
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(split_whitespace, delimiter = ";")]
+///   no_proxy: Vec<String>,
+///}
+/// ```
+/// `VecDeque<T>`, `HashSet<T>` and `BTreeSet<T>` are read the same way,
+/// with the sets dropping repeated elements.
+/// Elements are parsed like fields of their type, so lists of durations, booleans or
+/// non-zero integers work too, and an `Option` around the collection is `None` when unset.
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::{BTreeSet, HashSet};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SET_EXAMPLE_FEATURES"]
+///   features: HashSet<String>,
+///   #[env = "SET_EXAMPLE_PORTS"]
+///   ports: BTreeSet<u16>,
+///}
+/// # std::env::set_var("SET_EXAMPLE_FEATURES", "search,metrics,search");
+/// # std::env::set_var("SET_EXAMPLE_PORTS", "8080,80,443");
+/// let config = Config::new();
+/// assert_eq!(config.features.len(), 2);
+/// assert!(config.features.contains("metrics"));
+/// assert_eq!(config.ports.into_iter().collect::<Vec<_>>(), [80, 443, 8080]);
+/// ```
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "NESTED_EXAMPLE_EXTRA_HOSTS"]
+///   extra_hosts: Option<Vec<String>>,
+///   #[env = "NESTED_EXAMPLE_RETRIES"]
+///   retries: Option<Vec<Duration>>,
+///   #[env = "NESTED_EXAMPLE_TIMEOUTS"]
+///   timeouts: HashMap<String, Duration>,
+///}
+/// # std::env::set_var("NESTED_EXAMPLE_RETRIES", "100ms, 1s, 5s");
+/// # std::env::set_var("NESTED_EXAMPLE_TIMEOUTS", "read=5s,write=1m");
+/// let config = Config::new();
+/// assert_eq!(config.extra_hosts, None);
+/// assert_eq!(config.retries.unwrap()[2], Duration::from_secs(5));
+/// assert_eq!(config.timeouts["write"], Duration::from_secs(60));
+/// ```
+///
+/// Fixed-size arrays like `[T; N]` are read the same way, but the value must have exactly `N` elements.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "ARRAY_EXAMPLE_ORIGIN"]
+///   origin: [f64; 3],
+///}
+/// # std::env::set_var("ARRAY_EXAMPLE_ORIGIN", "1.0, 2.0, 3.0");
+/// assert_eq!(Config::new().origin, [1.0, 2.0, 3.0]);
+/// # std::env::set_var("ARRAY_EXAMPLE_ORIGIN", "1.0,2.0");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "ARRAY_EXAMPLE_ORIGIN expected 3 values, got 2");
+/// ```
+///
+/// ## Maps
+/// `HashMap<K, V>` and `BTreeMap<K, V>` fields are read from `key=value` entries separated by commas,
+/// or by the `env(delimiter = "...")` separator. Keys and values are parsed with `FromStr`.
+/// A key written more than once is an error rather than silently overwritten.
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::{BTreeMap, HashMap};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "MAP_EXAMPLE_FLAGS"]
+///   flags: HashMap<String, u8>,
+///   #[env = "MAP_EXAMPLE_LABELS"]
+///   labels: BTreeMap<String, String>,
+///}
+/// # std::env::set_var("MAP_EXAMPLE_FLAGS", "a=1, b=0,c=1");
+/// # std::env::set_var("MAP_EXAMPLE_LABELS", "team=core,env=prod,team=infra");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable MAP_EXAMPLE_LABELS=\"team=infra\": duplicate key 'team'"
+/// );
+/// # std::env::set_var("MAP_EXAMPLE_LABELS", "team=core,env=prod");
+/// let config = Config::new();
+/// assert_eq!(config.flags["a"], 1);
+/// assert_eq!(config.flags["b"], 0);
+/// assert_eq!(config.flags.len(), 3);
+/// assert_eq!(config.labels.keys().collect::<Vec<_>>(), ["env", "team"]);
+/// ```
+///
+/// ## Custom parsing
+/// The `env(parse_with = "...")` attribute names a `fn(&str) -> Result<T, E>` to use instead of `FromStr`.
+/// Its errors are reported just like parse failures.
+/// For `Option<T>` fields the function produces the inner `T`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Debug, PartialEq)]
+/// enum Mode { Fast, Safe }
+///
+/// fn parse_modes(value: &str) -> Result<Vec<Mode>, String> {
+///     value.split('+').map(|mode| match mode {
+///         "fast" => Ok(Mode::Fast),
+///         "safe" => Ok(Mode::Safe),
+///         other => Err(format!("unknown mode {}", other)),
+///     }).collect()
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PARSE_WITH_EXAMPLE_MODES"]
+///   #[env(parse_with = "parse_modes")]
+///   modes: Vec<Mode>,
+///}
+/// # std::env::set_var("PARSE_WITH_EXAMPLE_MODES", "fast+safe");
+/// let config = Config::new();
+/// assert_eq!(config.modes, vec![Mode::Fast, Mode::Safe]);
+/// ```
+///
+/// The function is checked against that signature where the attribute names it, so a function
+/// that doesn't exist or returns the wrong type is reported at the attribute.
+/// The same goes for `transform`, `default_fn` and `validate`.
+/// ```compile_fail
+/// use envar::Envar;
+/// fn parse_port(value: &str) -> Result<u32, std::num::ParseIntError> {
+///     value.parse()
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(parse_with = "parse_port")]
+///   port: u16,
+///}
+/// ```
+///
+/// ## Transforming values
+/// The `env(transform = "...")` attribute names a `fn(String) -> String` that rewrites the value
+/// read from the environment before it is parsed as usual. It runs after `trim`, and defaults
+/// are not transformed.
+/// ```rust
+/// use envar::Envar;
+/// fn lowercase(value: String) -> String {
+///     value.to_lowercase()
+/// }
+///
+/// fn strip_version_prefix(value: String) -> String {
+///     value.trim_start_matches('v').to_string()
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "TRANSFORM_EXAMPLE_LEVEL"]
+///   #[env(trim, transform = "lowercase")]
+///   level: String,
+///   #[env = "TRANSFORM_EXAMPLE_VERSION"]
+///   #[env(transform = "strip_version_prefix")]
+///   version: u32,
+///}
+/// # std::env::set_var("TRANSFORM_EXAMPLE_LEVEL", " DEBUG ");
+/// # std::env::set_var("TRANSFORM_EXAMPLE_VERSION", "v3");
+/// let config = Config::new();
+/// assert_eq!(config.level, "debug");
+/// assert_eq!(config.version, 3);
+/// ```
+///
+/// For the common case of case-insensitive values, `env(lowercase)` and `env(uppercase)`
+/// normalize the value instead, also after `trim` and before any parsing, including `parse_with`
+/// and `EnvarEnum` matching.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "CASE_EXAMPLE_LOG_FORMAT"]
+///   #[env(lowercase)]
+///   log_format: String,
+///   #[env = "CASE_EXAMPLE_REGION"]
+///   #[env(trim, uppercase)]
+///   region: String,
+///}
+/// # std::env::set_var("CASE_EXAMPLE_LOG_FORMAT", "JSON");
+/// # std::env::set_var("CASE_EXAMPLE_REGION", " eu-west-1 ");
+/// let config = Config::new();
+/// assert_eq!(config.log_format, "json");
+/// assert_eq!(config.region, "EU-WEST-1");
+/// ```
+///
+/// ## Explicitly unset values
+/// Some config systems can't remove a variable, and set it to a placeholder such as `none` instead.
+/// `env(none_values = [...])` lists such placeholders for an `Option` field, which is then `None`
+/// when its value matches one of them, ignoring case. A default isn't used in that case.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "NONE_VALUES_EXAMPLE_PROXY"]
+///   #[env(none_values = ["none", "null", ""])]
+///   proxy: Option<String>,
+///}
+/// # std::env::set_var("NONE_VALUES_EXAMPLE_PROXY", "NULL");
+/// assert_eq!(Config::new().proxy, None);
+/// # std::env::set_var("NONE_VALUES_EXAMPLE_PROXY", "http://proxy:3128");
+/// assert_eq!(Config::new().proxy.as_deref(), Some("http://proxy:3128"));
+/// ```
+///
+/// ## Lenient parsing
+/// Normally an `Option` field is `None` only when its variable is unset, and a value that doesn't
+/// parse is an error. With `env(lenient)` such a value is `None` too, with a warning, so an older
+/// binary keeps starting when a newer deployment sets a value it doesn't understand yet.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LENIENT_EXAMPLE_WORKERS"]
+///   #[env(lenient)]
+///   workers: Option<u16>,
+///}
+/// # std::env::set_var("LENIENT_EXAMPLE_WORKERS", "auto");
+/// assert_eq!(Config::new().workers, None);
+/// # std::env::set_var("LENIENT_EXAMPLE_WORKERS", "4");
+/// assert_eq!(Config::new().workers, Some(4));
+/// ```
+///
+/// ## Conditional requirements
+/// `env(required_if = "OTHER")` makes an `Option` field required whenever the variable `OTHER`
+/// is set to a true value, one of `true`, `1`, `yes` or `on`. Otherwise the field stays optional.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = "REQUIRED_IF_EXAMPLE_")]
+/// struct Config {
+///   tls_enabled: bool,
+///   #[env(required_if = "TLS_ENABLED")]
+///   tls_cert_path: Option<String>,
+///}
+/// # std::env::set_var("REQUIRED_IF_EXAMPLE_TLS_ENABLED", "false");
+/// assert_eq!(Config::new().tls_cert_path, None);
+///
+/// std::env::set_var("REQUIRED_IF_EXAMPLE_TLS_ENABLED", "true");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "REQUIRED_IF_EXAMPLE_TLS_CERT_PATH is required because REQUIRED_IF_EXAMPLE_TLS_ENABLED is true"
+/// );
+/// ```
+///
+/// `env(requires = "OTHER")` goes further and doesn't read the field at all unless `OTHER` is true,
+/// leaving it at `Default::default()`, which is `None` for an `Option`. Fields that belong to a
+/// feature can then be required without breaking deployments where the feature is off.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = "REQUIRES_EXAMPLE_")]
+/// struct Config {
+///   metrics_enabled: bool,
+///   #[env(requires = "METRICS_ENABLED")]
+///   metrics_port: u16,
+///   #[env(requires = "METRICS_ENABLED")]
+///   metrics_path: Option<String>,
+///}
+/// # std::env::set_var("REQUIRES_EXAMPLE_METRICS_ENABLED", "false");
+/// let config = Config::new();
+/// assert_eq!(config.metrics_port, 0);
+/// assert_eq!(config.metrics_path, None);
+///
+/// std::env::set_var("REQUIRES_EXAMPLE_METRICS_ENABLED", "true");
+/// assert!(Config::try_new().is_err());
+/// std::env::set_var("REQUIRES_EXAMPLE_METRICS_PORT", "9090");
+/// assert_eq!(Config::new().metrics_port, 9090);
+/// ```
+///
+/// On a field with both, `requires` is checked first: while its variable is off the field is `None`
+/// whatever `required_if` says, and once it is on the field is read as an optional field that
+/// `required_if` can make required.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = "REQUIRES_REQUIRED_IF_EXAMPLE_")]
+/// struct Config {
+///   tls_enabled: bool,
+///   client_auth: bool,
+///   #[env(requires = "TLS_ENABLED", required_if = "CLIENT_AUTH")]
+///   client_ca_path: Option<String>,
+///}
+/// # std::env::set_var("REQUIRES_REQUIRED_IF_EXAMPLE_TLS_ENABLED", "false");
+/// # std::env::set_var("REQUIRES_REQUIRED_IF_EXAMPLE_CLIENT_AUTH", "true");
+/// // Client auth is on, but TLS isn't, so the path isn't needed
+/// assert_eq!(Config::new().client_ca_path, None);
+///
+/// std::env::set_var("REQUIRES_REQUIRED_IF_EXAMPLE_TLS_ENABLED", "true");
+/// assert!(Config::try_new().is_err());
+/// std::env::set_var("REQUIRES_REQUIRED_IF_EXAMPLE_CLIENT_AUTH", "false");
+/// assert_eq!(Config::new().client_ca_path, None);
+/// ```
+///
+/// ## Handling errors
+/// `try_new()` returns an error instead of panicking, so the caller decides how to report it.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "TRY_NEW_PORT"]
+///   port: u16,
+///}
+/// # std::env::set_var("TRY_NEW_PORT", "not a number");
+/// match Config::try_new() {
+///     Ok(config) => println!("Port: {}", config.port),
+///     Err(e) => eprintln!("{e}"),
+/// }
+/// ```
+///
+/// A value that fails to parse is included in the error, and so in the panic of `new()`.
+/// For `env(secret)` and `Secret<T>` fields each of its characters is replaced by `*`,
+/// so only its length shows, and errors from validation leave out the value they received.
+/// ```rust
+/// use envar::{Envar, EnvarError};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PARSE_VALUE_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env(name = "PARSE_VALUE_EXAMPLE_PIN", secret)]
+///   pin: u32,
+///}
+/// # std::env::set_var("PARSE_VALUE_EXAMPLE_PORT", "80a");
+/// # std::env::set_var("PARSE_VALUE_EXAMPLE_PIN", "12x4");
+/// let EnvarError::Multiple(errors) = Config::try_new().err().unwrap() else { panic!() };
+/// assert_eq!(
+///     errors[0].to_string(),
+///     "Failed to parse environment variable PARSE_VALUE_EXAMPLE_PORT=\"80a\": invalid digit found in string"
+/// );
+/// assert_eq!(
+///     errors[1].to_string(),
+///     "Failed to parse environment variable PARSE_VALUE_EXAMPLE_PIN=\"****\": invalid digit found in string"
+/// );
+/// ```
+///
+/// An integer too large or too small for its field is reported as out of range,
+/// rather than as a malformed number.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "OVERFLOW_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// # std::env::set_var("OVERFLOW_EXAMPLE_PORT", "70000");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "OVERFLOW_EXAMPLE_PORT value 70000 is out of range for u16");
+/// ```
+///
+/// The `env(error = "...")` attribute replaces the message reported when a required variable is
+/// missing, to tell operators what the variable is for. It is used by `new()` when it panics too.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "ERROR_EXAMPLE_DATABASE_URL"]
+///   #[env(error = "Set ERROR_EXAMPLE_DATABASE_URL to your Postgres connection string")]
+///   database_url: String,
+///}
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(err.to_string(), "Set ERROR_EXAMPLE_DATABASE_URL to your Postgres connection string");
+/// assert!(matches!(err, envar::EnvarError::Custom { .. }));
+/// ```
+///
+/// `envar(error = "...")` names the application's own error type for `try_new()` and `reload()` to
+/// return, converted from `envar::EnvarError` through its `From` implementation. The other loaders,
+/// such as `from_source()` and `collect()`, keep returning envar's errors.
+/// ```rust
+/// use envar::{Envar, EnvarError};
+/// #[derive(Debug)]
+/// enum AppError {
+///     Config(EnvarError),
+/// }
+///
+/// impl From<EnvarError> for AppError {
+///     fn from(err: EnvarError) -> Self {
+///         AppError::Config(err)
+///     }
+/// }
+///
+/// #[derive(Envar)]
+/// #[envar(error = "AppError")]
+/// struct Config {
+///   #[env = "ERROR_TYPE_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// fn load() -> Result<Config, AppError> {
+///     let config = Config::try_new()?;
+///     Ok(config)
+/// }
+/// assert!(matches!(load(), Err(AppError::Config(EnvarError::Missing { .. }))));
+/// ```
+///
+/// A field of type `Result<T, String>` or `Result<T, envar::EnvarError>` keeps the outcome of
+/// reading its `T` instead of failing the whole struct, for tools that report on every variable.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "RESULT_EXAMPLE_PORT"]
+///   port: Result<u16, String>,
+///   #[env = "RESULT_EXAMPLE_WORKERS"]
+///   workers: Result<u16, envar::EnvarError>,
+///   #[env = "RESULT_EXAMPLE_HOST"]
+///   host: Result<Option<String>, String>,
+///}
+/// # std::env::set_var("RESULT_EXAMPLE_PORT", "http");
+/// let config = Config::new();
+/// assert_eq!(
+///     config.port,
+///     Err("Failed to parse environment variable RESULT_EXAMPLE_PORT=\"http\": invalid digit found in string".to_string())
+/// );
+/// assert!(matches!(config.workers, Err(envar::EnvarError::Missing { .. })));
+/// assert_eq!(config.host, Ok(None));
+/// ```
+///
+/// ## Reloading
+/// `reload()` re-reads every field in place, for example after a `SIGHUP`.
+/// All fields are read before any are written, so a failed reload leaves the old values intact.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "RELOAD_EXAMPLE_WORKERS"]
+///   workers: u16,
+///}
+/// # std::env::set_var("RELOAD_EXAMPLE_WORKERS", "4");
+/// let mut config = Config::new();
+/// std::env::set_var("RELOAD_EXAMPLE_WORKERS", "8");
+/// config.reload().unwrap();
+/// assert_eq!(config.workers, 8);
+///
+/// std::env::set_var("RELOAD_EXAMPLE_WORKERS", "many");
+/// assert!(config.reload().is_err());
+/// assert_eq!(config.workers, 8);
+/// ```
+///
+/// ## Lazy fields
+/// `env(lazy)` defers reading a field until it is first used. A derive can't change the type of a
+/// field, so the field is declared as a `std::sync::OnceLock<T>`, which the config leaves empty.
+/// A `name()` accessor reads and parses the variable the first time it is called and keeps the value,
+/// panicking like `new()` if it can't, and `try_name()` returns the error instead.
+/// Fields that only some code paths use then cost nothing, and can't fail, at startup.
+///
+/// The accessor reads the process environment, and the `envar(dotenv)` file if the struct has one,
+/// whatever source the rest of the config came from. Until it is called the field shows as
+/// `<unread>` in `envar(display)`, and is left out of `as_map()`.
+/// ```rust
+/// use envar::Envar;
+/// use std::sync::OnceLock;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LAZY_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env(name = "LAZY_EXAMPLE_DATABASE_URL", lazy)]
+///   database_url: OnceLock<String>,
+///   #[env(name = "LAZY_EXAMPLE_REPORT_WORKERS", lazy)]
+///   report_workers: OnceLock<u16>,
+///}
+/// # std::env::set_var("LAZY_EXAMPLE_PORT", "8080");
+/// // Neither variable is set yet, but they aren't needed to build the config
+/// let config = Config::new();
+/// std::env::set_var("LAZY_EXAMPLE_DATABASE_URL", "postgres://localhost");
+/// assert_eq!(config.database_url(), "postgres://localhost");
+/// std::env::set_var("LAZY_EXAMPLE_DATABASE_URL", "postgres://elsewhere");
+/// assert_eq!(config.database_url(), "postgres://localhost");
+/// assert!(config.try_report_workers().is_err());
+/// ```
+///
+/// A field of type `envar::Lazy<T>` is the other way to defer work: its variable is looked up with
+/// the rest of the config, from the same source, but it is only parsed the first time `get()` or
+/// `try_get()` is called, and a malformed value is only reported then.
+/// ```rust
+/// use envar::{Envar, Lazy};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LAZY_TYPE_FIELD_EXAMPLE_WORKERS"]
+///   workers: Lazy<u16>,
+///}
+/// # std::env::set_var("LAZY_TYPE_FIELD_EXAMPLE_WORKERS", "four");
+/// let config = Config::new();
+/// assert!(config.workers.try_get().is_err());
+/// ```
+///
+/// ## Global configs
+/// With `envar(singleton)`, `global()` returns a `&'static` config that is read from the environment
+/// the first time it is called and shared by every later call, on any thread.
+/// Like `new()` it panics if the config can't be read; `try_global()` returns the error instead,
+/// and a later call tries again.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(singleton)]
+/// struct Config {
+///   #[env = "GLOBAL_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// assert!(Config::try_global().is_err());
+/// std::env::set_var("GLOBAL_EXAMPLE_PORT", "8080");
+/// assert_eq!(Config::global().port, 8080);
+/// std::env::set_var("GLOBAL_EXAMPLE_PORT", "9090");
+/// assert_eq!(Config::global().port, 8080);
+/// ```
+///
+/// ## Other sources
+/// `from_source()` reads the variables from any `envar::EnvSource` instead of the process environment,
+/// such as a `HashMap<String, String>`. This keeps tests independent of the global environment.
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::HashMap;
+/// #[derive(Envar)]
+/// struct Config {
+///   port: u16,
+///   debug: Option<bool>,
+///}
+/// let vars = HashMap::from([("PORT".to_string(), "8080".to_string())]);
+/// let config = Config::from_source(&vars).unwrap();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.debug, None);
+/// ```
+///
+/// The struct also implements `TryFrom<&HashMap<String, String>>`, for variables that were
+/// fetched from somewhere else, like a secrets manager.
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::HashMap;
+/// #[derive(Envar)]
+/// struct Config {
+///   port: u16,
+///}
+/// let vars = HashMap::from([("PORT".to_string(), "8080".to_string())]);
+/// let config = Config::try_from(&vars).unwrap();
+/// assert_eq!(config.port, 8080);
+/// ```
+///
+/// `from_sources()` merges several sources, looking each variable up in them in order:
+/// the first source that has a variable wins.
+/// ```rust
+/// use envar::{Envar, StdEnv};
+/// use std::collections::HashMap;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FROM_SOURCES_EXAMPLE_HOST"]
+///   host: String,
+///   #[env = "FROM_SOURCES_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// let mounted = HashMap::from([
+///     ("FROM_SOURCES_EXAMPLE_HOST".to_string(), "db.internal".to_string()),
+///     ("FROM_SOURCES_EXAMPLE_PORT".to_string(), "5432".to_string()),
+/// ]);
+/// # std::env::set_var("FROM_SOURCES_EXAMPLE_PORT", "6432");
+/// let config = Config::from_sources(&[&StdEnv, &mounted]).unwrap();
+/// assert_eq!(config.host, "db.internal");
+/// assert_eq!(config.port, 6432);
+/// ```
+///
+/// `from_env_snapshot()` copies the whole environment once with [`Snapshot::capture`](envar::Snapshot::capture)
+/// and reads every field from that copy, so the struct is consistent even if another thread
+/// changes a variable while it is being read.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SNAPSHOT_EXAMPLE_HOST"]
+///   host: String,
+///   #[env = "SNAPSHOT_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// # std::env::set_var("SNAPSHOT_EXAMPLE_HOST", "localhost");
+/// # std::env::set_var("SNAPSHOT_EXAMPLE_PORT", "8080");
+/// let config = Config::from_env_snapshot().unwrap();
+/// assert_eq!(config.host, "localhost");
+/// assert_eq!(config.port, 8080);
+/// ```
+///
+/// ## Command-line arguments
+/// `from_env_and_args()` looks every variable up in a map of arguments first, and then in the environment,
+/// so a flag parsed by a CLI library overrides the variable. Arguments are keyed by the variable name,
+/// or by the key given with `env(arg = "...")`.
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::HashMap;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(name = "ARGS_EXAMPLE_PORT", arg = "--port")]
+///   port: u16,
+///   #[env = "ARGS_EXAMPLE_HOST"]
+///   host: String,
+///}
+/// # std::env::set_var("ARGS_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("ARGS_EXAMPLE_HOST", "localhost");
+/// let args = HashMap::from([("--port".to_string(), "9090".to_string())]);
+/// let config = Config::from_env_and_args(&args).unwrap();
+/// assert_eq!(config.port, 9090);
+/// assert_eq!(config.host, "localhost");
+/// ```
+///
+/// ## Reporting every problem at once
+/// `collect()` reads every field before giving up and returns all the errors it found.
+/// `try_new()` and `new()` report all of them too, so a broken `.env` can be fixed in one pass.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "COLLECT_PORT"]
+///   port: u16,
+///   #[env = "COLLECT_HOST"]
+///   host: String,
+///}
+/// # std::env::set_var("COLLECT_PORT", "not a number");
+/// match Config::collect() {
+///     Ok(config) => println!("Port: {}", config.port),
+///     Err(errors) => {
+///         for e in &errors {
+///             eprintln!("{e}");
+///         }
+///         # assert_eq!(errors.len(), 2);
+///     }
+/// }
+/// ```
+///
+/// `validate()` runs the same checks as a preflight, for example in a healthcheck,
+/// returning every problem without handing back a config.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "VALIDATE_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env = "VALIDATE_EXAMPLE_WORKERS"]
+///   workers: u16,
+///}
+/// # std::env::set_var("VALIDATE_EXAMPLE_PORT", "8080");
+/// # std::env::set_var("VALIDATE_EXAMPLE_WORKERS", "many");
+/// let errors = Config::validate().unwrap_err();
+/// assert_eq!(errors.len(), 1);
+/// # std::env::set_var("VALIDATE_EXAMPLE_WORKERS", "4");
+/// assert!(Config::validate().is_ok());
+/// ```
+///
+/// ## Tracing
+/// With the `log` feature enabled, `envar(trace)` logs at debug level how every variable was resolved:
+/// whether it was set, through which name, and whether its default was used. Values are never logged,
+/// so secrets are safe to trace.
+/// ```rust
+/// use envar::Envar;
+/// use std::sync::Mutex;
+/// static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+///
+/// struct Logger;
+/// impl log::Log for Logger {
+///     fn enabled(&self, _: &log::Metadata) -> bool { true }
+///     fn log(&self, record: &log::Record) { LINES.lock().unwrap().push(record.args().to_string()) }
+///     fn flush(&self) {}
+/// }
+///
+/// #[derive(Envar)]
+/// #[envar(trace)]
+/// struct Config {
+///   #[env(name = "TRACE_EXAMPLE_PASSWORD", aliases = ["TRACE_EXAMPLE_PASS"], secret)]
+///   password: String,
+///   #[env = "TRACE_EXAMPLE_PORT"]
+///   #[default = "8080"]
+///   port: u16,
+///}
+/// log::set_logger(&Logger).unwrap();
+/// log::set_max_level(log::LevelFilter::Debug);
+/// # std::env::set_var("TRACE_EXAMPLE_PASS", "hunter2");
+/// Config::new();
+/// assert_eq!(*LINES.lock().unwrap(), [
+///     "TRACE_EXAMPLE_PASSWORD is set through TRACE_EXAMPLE_PASS",
+///     "TRACE_EXAMPLE_PORT is not set, using its default",
+/// ]);
+/// ```
+///
+/// ## Listing variables
+/// `env_var_names()` returns the name of every variable the struct reads, after prefixes are applied.
+/// Names that come from a prefixed `flatten` field are built on each call, so the list is a
+/// `Vec<Cow<'static, str>>`.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = "APP_")]
+/// struct Config {
+///   port: u16,
+///   #[env = "HOSTNAME"]
+///   host: String,
+///}
+/// assert_eq!(Config::env_var_names(), ["APP_PORT", "APP_HOSTNAME"]);
+/// ```
+///
+/// `example_env()` renders the same list as a `.env` template, which is handy for keeping a
+/// `.env.example` file up to date. Optional fields and defaults are noted in comments,
+/// and `env(example = "...")` fills in a sample value.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[default = "8080"]
+///   port: u16,
+///   #[env(example = "postgres://localhost/app")]
+///   database_url: String,
+///   debug: Option<bool>,
+///}
+/// assert_eq!(
+///     Config::example_env(),
+///     "# Default: 8080\nPORT=\nDATABASE_URL=postgres://localhost/app\n# Optional\nDEBUG=\n",
+/// );
+/// ```
+///
+/// ## Tuple structs
+/// Tuple structs are supported too. Their fields have no name to derive a variable name from,
+/// so each one needs an explicit `env` attribute.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Port(#[env = "TUPLE_EXAMPLE_PORT"] u16);
+///
+/// # std::env::set_var("TUPLE_EXAMPLE_PORT", "8080");
+/// let port = Port::new();
+/// assert_eq!(port.0, 8080);
+/// ```
+///
+/// ## Attribute errors
+/// Unknown keys and malformed values in `env(...)` are compile errors pointing at the offending
+/// token, so a typo such as `defualt` can't be silently ignored.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env(defualt = "8080")]
+///   port: u16,
+///}
+/// ```
+///
+/// The same goes for `envar(...)` on the struct, whether the key is unknown or its value has the wrong type.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(singelton)]
+/// struct Config {
+///   port: u16,
+///}
+/// ```
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// #[envar(prefix = 5)]
+/// struct Config {
+///   port: u16,
+///}
+/// ```
+///
+/// Giving a field its variable name twice is an error too.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PORT"]
+///   #[env(name = "HTTP_PORT")]
+///   port: u16,
+///}
+/// ```
+///
+/// Two fields reading the same variable, by name or through an alias, are rejected as well.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "PORT"]
+///   http_port: u16,
+///   port: u16,
+///}
+/// ```
+///
+/// A field marked `env(shared)` is the exception, for keeping a second view of a variable that another
+/// field reads, such as the raw string next to its parsed value. It is left out of `env_var_names()`
+/// and the other listings, which already have the variable.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SHARED_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env(name = "SHARED_EXAMPLE_PORT", shared)]
+///   raw_port: String,
+///}
+/// # std::env::set_var("SHARED_EXAMPLE_PORT", "08080");
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.raw_port, "08080");
+/// assert_eq!(Config::env_var_names(), ["SHARED_EXAMPLE_PORT"]);
+/// ```
+///
+/// ## Skipping fields
+/// Fields marked with `env(skip)` are never read from the environment and start out as
+/// `Default::default()`, for values that are computed or filled in later.
+/// ```rust
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SKIP_EXAMPLE_PORT"]
+///   port: u16,
+///   #[env(skip)]
+///   connections: Vec<String>,
+///}
+/// # std::env::set_var("SKIP_EXAMPLE_PORT", "8080");
+/// let config = Config::new();
+/// assert!(config.connections.is_empty());
+/// assert_eq!(Config::env_var_names(), ["SKIP_EXAMPLE_PORT"]);
+/// ```
+///
+/// ## Generic structs
+/// Generic structs are supported, as long as every field read from the environment has a type
+/// that can be parsed. `PhantomData` fields are never read, so a struct can carry a type marker.
+/// ```rust
+/// use envar::Envar;
+/// use std::marker::PhantomData;
+/// struct Postgres;
+/// #[derive(Envar)]
+/// struct Database<Backend> {
+///   #[env = "GENERIC_EXAMPLE_DATABASE_URL"]
+///   url: String,
+///   backend: PhantomData<Backend>,
+///}
+/// # std::env::set_var("GENERIC_EXAMPLE_DATABASE_URL", "postgres://localhost/app");
+/// let database = Database::<Postgres>::new();
+/// assert_eq!(database.url, "postgres://localhost/app");
+/// assert_eq!(Database::<Postgres>::env_var_names(), ["GENERIC_EXAMPLE_DATABASE_URL"]);
+/// ```
+///
+/// ## Hygiene
+/// The generated code names everything by its full path, so local items that shadow `std`, `core`
+/// or prelude names such as `Result`, `Option`, `String` or `Default` don't break it.
+/// ```rust
+/// # #![allow(dead_code, non_camel_case_types)]
+/// mod std {}
+/// mod core {}
+/// struct Result;
+/// struct Option;
+/// struct String;
+/// struct Vec;
+/// struct Default;
+/// struct Ok;
+/// struct Some;
+/// struct None;
+/// fn drop() {}
+///
+/// #[derive(envar::EnvarEnum, Debug)]
+/// enum Mode { Fast, Safe }
+///
+/// #[derive(envar::Envar)]
+/// #[envar(derive_debug, derive_default, display, as_map)]
+/// struct Config {
+///   #[env = "HYGIENE_EXAMPLE_PORT"]
+///   #[default = "8080"]
+///   port: u16,
+///   #[env = "HYGIENE_EXAMPLE_HOSTS"]
+///   #[default = "a,b"]
+///   hosts: ::std::vec::Vec<::std::string::String>,
+///   #[env = "HYGIENE_EXAMPLE_TIMEOUT"]
+///   timeout: ::core::option::Option<::std::time::Duration>,
+///}
+///
+/// #[derive(envar::Envar)]
+/// struct Settings {
+///   #[env = "HYGIENE_EXAMPLE_MODE"]
+///   #[default = "safe"]
+///   mode: Mode,
+///}
+/// let config = Config::new();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.hosts.len(), 2);
+/// assert!(matches!(Settings::new().mode, Mode::Safe));
+/// ```
+///
+/// ## Supported types
+/// Envar can only be derived for structs with fields.
+/// Anything else is rejected with an error pointing at the offending type.
+/// Enums can be read as field values instead, with `EnvarEnum`.
+/// ```compile_fail
+/// use envar::Envar;
+/// #[derive(Envar)]
+/// enum Config {
+///   Production,
+///   Development,
+///}
+/// ```
+///
+///
+#[proc_macro_derive(Envar, attributes(env, default, envar))]
+pub fn env_new(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_envar(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+// Generates the inherent methods and trait implementations of `#[derive(Envar)]`
+fn derive_envar(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = input.ident;
+    let container = parse_container_attrs(&input.attrs)?;
+    let (fields, is_tuple) = match input.data {
+        Data::Struct(data) => match data.fields {
+            Fields::Named(fields) => (fields.named, false),
+            Fields::Unnamed(fields) => (fields.unnamed, true),
+            Fields::Unit => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "Envar cannot be derived for unit structs, they have no fields to read",
+                ));
+            }
+        },
+        Data::Enum(_) | Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Envar can only be derived for structs",
+            ));
+        }
+    };
+
+    let fields = parse_fields(&fields)?;
+
+    // Tuple struct fields have no name to derive a variable name from
+    if is_tuple {
+        for field in &fields {
+            if field.attrs.name.is_none() && !field.attrs.flatten && !field.is_skipped() {
+                return Err(syn::Error::new_spanned(
+                    field.field,
+                    "tuple struct fields need an explicit #[env = \"...\"] variable name",
+                ));
+            }
+            if field.attrs.flatten_prefix == Some(None) {
+                return Err(syn::Error::new_spanned(
+                    field.field,
+                    "tuple struct fields need an explicit envar(prefix = \"...\")",
+                ));
+            }
+        }
+    }
+
+    check_duplicate_names(&fields, &container)?;
+    for field in fields.iter().filter(|field| field.attrs.lazy) {
+        check_lazy(field)?;
+    }
+    if container.trace && !cfg!(feature = "log") {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "the `log` feature of envar must be enabled to use `envar(trace)`",
+        ));
+    }
+
+    let read_fields = if container.serde {
+        generate_serde_read(&fields, &container, is_tuple)?
+    } else {
+        let field_members = fields.iter().map(|field| &field.member);
+        let field_names: Vec<_> = fields.iter().map(EnvField::local).collect();
+        let field_values = fields
+            .iter()
+            .map(|field| generate_field_init(field, &container));
+        quote! {
+            let mut __errors = ::std::vec::Vec::new();
+            #(
+                let #field_names = match #field_values {
+                    ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                    ::core::result::Result::Err(::envar::EnvarError::Multiple(errors)) => {
+                        __errors.extend(errors);
+                        ::core::option::Option::None
+                    }
+                    ::core::result::Result::Err(err) => {
+                        __errors.push(err);
+                        ::core::option::Option::None
+                    }
+                };
+            )*
+            if !__errors.is_empty() {
+                return ::core::result::Result::Err(__errors);
+            }
+            ::core::result::Result::Ok(Self {
+                #(#field_members: #field_names.unwrap(),)*
+            })
+        }
+    };
+    let env_var_names = generate_env_var_names(&fields, &container);
+    let example_env = generate_example_env(&fields, &container);
+    let lazy_accessors = generate_lazy_accessors(&fields, &container);
+    let arg_names = generate_arg_names(&fields, &container);
+    // Arguments take precedence over the process environment, which takes precedence over
+    // the `.env` file, and defaults come last
+    let read_env = |setup: proc_macro2::TokenStream, env: proc_macro2::TokenStream| match &container
+        .dotenv
+    {
+        Some(path) => quote! {
+            #setup
+            let dotenv = match ::envar::dotenv::read(#path) {
+                ::core::result::Result::Ok(vars) => vars,
+                ::core::result::Result::Err(source) => {
+                    return ::core::result::Result::Err(::std::vec![::envar::EnvarError::Dotenv { path: #path.to_string(), source }]);
+                }
+            };
+            let layered = ::envar::Layered::new().with(#env).with(&dotenv);
+            Self::__envar_collect(&::envar::Prefixed::new(prefix, &layered))
+        },
+        None => quote! {
+            #setup
+            Self::__envar_collect(&::envar::Prefixed::new(prefix, #env))
+        },
+    };
+    let collect = read_env(quote! {}, quote! { &::envar::StdEnv });
+    let collect_with_args = read_env(
+        quote! {
+            let names = Self::__envar_arg_names();
+            let args = ::envar::__private::Args::new(args, &names);
+            let env = ::envar::Layered::new().with(&args).with(&::envar::StdEnv);
+        },
+        quote! { &env },
+    );
+    let collect_snapshot = read_env(
+        quote! { let env = ::envar::Snapshot::capture(); },
+        quote! { &env },
+    );
+
+    let debug_impl = if container.derive_debug {
+        generate_debug_impl(&name, &input.generics, &fields, is_tuple)
+    } else {
+        quote! {}
+    };
+    let display_impl = if container.display {
+        generate_display_impl(&name, &input.generics, &fields, &container)
+    } else {
+        quote! {}
+    };
+    let default_impl = if container.derive_default {
+        generate_default_impl(&name, &input.generics, &fields, &container)?
+    } else {
+        quote! {}
+    };
+
+    let as_map = if container.as_map {
+        generate_as_map(&fields, &container)
+    } else {
+        quote! {}
+    };
+    let global = if container.singleton {
+        generate_global(&name, &input.generics)?
+    } else {
+        quote! {}
+    };
+
+    // A custom error type is only used where the struct is loaded from the process environment
+    let (try_new, error_ty) = match &container.error {
+        Some(error_ty) => (
+            quote! {
+                pub fn try_new() -> ::core::result::Result<Self, #error_ty> {
+                    Self::collect().map_err(|errors| <#error_ty as ::core::convert::From<::envar::EnvarError>>::from(::envar::EnvarError::from(errors)))
+                }
+            },
+            quote! { #error_ty },
+        ),
+        None => (
+            quote! {
+                pub fn try_new() -> ::core::result::Result<Self, ::envar::EnvarError> {
+                    Self::collect().map_err(::envar::EnvarError::from)
+                }
+            },
+            quote! { ::envar::EnvarError },
+        ),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #debug_impl
+
+        #default_impl
+
+        #display_impl
+
+        #[automatically_derived]
+        impl #impl_generics ::std::convert::TryFrom<&::std::collections::HashMap<::std::string::String, ::std::string::String>> for #name #ty_generics #where_clause {
+            type Error = ::envar::EnvarError;
+
+            fn try_from(vars: &::std::collections::HashMap<::std::string::String, ::std::string::String>) -> ::core::result::Result<Self, Self::Error> {
+                Self::from_source(vars)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::envar::FromEnv for #name #ty_generics #where_clause {
+            fn from_env() -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::collect().map_err(::envar::EnvarError::from)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub fn collect() -> ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> {
+                Self::__envar_read_env("")
+            }
+
+            pub fn from_env_with_prefix(prefix: &str) -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::__envar_read_env(prefix).map_err(::envar::EnvarError::from)
+            }
+
+            #[doc(hidden)]
+            pub fn __envar_read_env(prefix: &str) -> ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> {
+                let result: ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> = { #collect };
+                result.map_err(|errors| ::envar::__private::prefix_errors(prefix, errors))
+            }
+
+            pub fn from_env_and_args(args: &::std::collections::HashMap<::std::string::String, ::std::string::String>) -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::__envar_read_env_and_args(args).map_err(::envar::EnvarError::from)
+            }
+
+            #[doc(hidden)]
+            pub fn __envar_read_env_and_args(args: &::std::collections::HashMap<::std::string::String, ::std::string::String>) -> ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> {
+                let prefix = "";
+                #collect_with_args
+            }
+
+            #arg_names
+
+            #(#lazy_accessors)*
+
+            pub fn from_env_snapshot() -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::__envar_read_env_snapshot().map_err(::envar::EnvarError::from)
+            }
+
+            #[doc(hidden)]
+            pub fn __envar_read_env_snapshot() -> ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> {
+                let prefix = "";
+                #collect_snapshot
+            }
+
+            pub fn from_source<S: ::envar::EnvSource + ?Sized>(source: &S) -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::__envar_collect(source).map_err(::envar::EnvarError::from)
+            }
+
+            pub fn from_sources(sources: &[&dyn ::envar::EnvSource]) -> ::core::result::Result<Self, ::envar::EnvarError> {
+                Self::from_source(&sources.iter().copied().collect::<::envar::Layered<'_>>())
+            }
+
+            #[doc(hidden)]
+            pub fn __envar_collect<S: ::envar::EnvSource + ?Sized>(__source: &S) -> ::core::result::Result<Self, ::std::vec::Vec<::envar::EnvarError>> {
+                #read_fields
+            }
+
+            pub fn validate() -> ::core::result::Result<(), ::std::vec::Vec<::envar::EnvarError>> {
+                Self::collect().map(::core::mem::drop)
+            }
+
+            #try_new
+
+            pub fn new() -> Self {
+                match Self::collect().map_err(::envar::EnvarError::from) {
+                    ::core::result::Result::Ok(config) => config,
+                    ::core::result::Result::Err(err) => ::core::panic!("{}", err),
+                }
+            }
+
+            pub fn reload(&mut self) -> ::core::result::Result<(), #error_ty> {
+                *self = Self::try_new()?;
+                ::core::result::Result::Ok(())
+            }
+
+            #env_var_names
+
+            #example_env
+
+            #global
+
+            #as_map
+        }
+    })
+}
+
+// Generates `global()` and `try_global()`, which read the config on first use and share it afterwards
+fn generate_global(name: &Ident, generics: &Generics) -> syn::Result<proc_macro2::TokenStream> {
+    // A static can't depend on the type parameters of the impl it is declared in
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &generics.params,
+            "`singleton` can't be used on generic structs",
+        ));
+    }
+    Ok(quote! {
+        pub fn try_global() -> ::core::result::Result<&'static Self, ::envar::EnvarError> {
+            static GLOBAL: ::envar::__private::Global<#name> = ::envar::__private::Global::new();
+            GLOBAL.get_or_try_init(|| Self::collect().map_err(::envar::EnvarError::from))
+        }
+
+        pub fn global() -> &'static Self {
+            match Self::try_global() {
+                ::core::result::Result::Ok(config) => config,
+                ::core::result::Result::Err(err) => ::core::panic!("{}", err),
+            }
+        }
+    })
+}
+
+// Generates a `Default` implementation from the field defaults, without reading any variables
+fn generate_default_impl(
+    name: &Ident,
+    generics: &Generics,
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut field_defaults = Vec::new();
+    for field in fields {
+        let (member, attrs, ty) = (&field.member, &field.attrs, &field.field.ty);
+        let lazy_ty = extract_generic_inner_type(ty, "Lazy");
+        let value_ty = lazy_ty.unwrap_or_else(|| extract_option_inner_type(ty).unwrap_or(ty));
+        if let Some(default_expr) = &attrs.default_expr {
+            return Err(syn::Error::new_spanned(
+                default_expr,
+                "envar(derive_default) can't evaluate a `default_expr`, which needs the other fields to be read",
+            ));
+        }
+        let default = if attrs.flatten || attrs.lazy || field.is_skipped() {
+            None
+        } else {
+            let env_var_name = resolve_env_var_name(field, container);
+            generate_default_value(value_ty, &env_var_name, attrs, &[])
+        };
+        let value = if attrs.flatten || attrs.lazy || field.is_skipped() {
+            quote! { ::core::default::Default::default() }
+        } else if let (Some(default), Some(_)) = (&default, lazy_ty) {
+            // A `Lazy` default is parsed on first use, like a value read from the environment
+            quote! { ::envar::Lazy::new(|| #default) }
+        } else if let Some(mut default) = default {
+            if is_option_type(ty) {
+                default = quote! { #default.map(::core::option::Option::Some) };
+            }
+            quote! {
+                match #default {
+                    ::core::result::Result::Ok(value) => value,
+                    ::core::result::Result::Err(err) => ::core::panic!("{}", err),
+                }
+            }
+        } else if is_option_type(ty) {
+            quote! { ::core::option::Option::None }
+        } else {
+            return Err(syn::Error::new_spanned(
+                field.field,
+                "envar(derive_default) needs a #[default = \"...\"], default_fn or default_const on every field that isn't an Option",
+            ));
+        };
+        field_defaults.push(quote! { #member: #value });
+    }
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#field_defaults,)*
+                }
+            }
+        }
+    })
+}
+
+// A struct field along with its parsed attributes
+struct EnvField<'a> {
+    field: &'a Field,
+    // How the field is accessed: by name, or by position for tuple structs
+    member: Member,
+    attrs: FieldAttrs,
+    // The fields declared before this one, which a `default_expr` can refer to
+    earlier: Vec<Ident>,
+}
+
+impl EnvField<'_> {
+    // Skipped fields are never read and start out as `Default::default()`.
+    // Type markers carry no value, so `PhantomData` is always skipped.
+    fn is_skipped(&self) -> bool {
+        self.attrs.skip || is_phantom_data(&self.field.ty)
+    }
+
+    // The local variable holding the field's value while the struct is being built
+    fn local(&self) -> Ident {
+        match &self.member {
+            Member::Named(ident) => ident.clone(),
+            Member::Unnamed(index) => format_ident!("__field{}", index.index),
+        }
+    }
+}
+
+// Parses the attributes of every field, reporting all attribute errors together
+fn parse_fields(fields: &Punctuated<Field, Token![,]>) -> syn::Result<Vec<EnvField<'_>>> {
+    let mut parsed = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for (index, field) in fields.iter().enumerate() {
+        match parse_field_attrs(&field.attrs) {
+            Ok(attrs) => parsed.push(EnvField {
+                field,
+                member: match &field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(index.into()),
+                },
+                attrs,
+                earlier: fields.iter().take(index).filter_map(|field| field.ident.clone()).collect(),
+            }),
+            Err(err) => match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(parsed),
+    }
+}
+
+// The variable a field is read from, after applying the prefix and naming rules
+fn resolve_env_var_name(field: &EnvField, container: &ContainerAttrs) -> String {
+    match (&field.attrs.name, &field.field.ident) {
+        (Some(name), _) => format!("{}{}", container.prefix, name),
+        (None, Some(field_name)) => format!(
+            "{}{}",
+            container.prefix,
+            container.rename_all.apply(field_name)
+        ),
+        // Rejected in `env_new` before any code is generated
+        (None, None) => unreachable!("tuple struct field without a variable name"),
+    }
+}
+
+// Rejects two fields reading the same variable, whether as their name or as an alias
+fn check_duplicate_names(fields: &[EnvField], container: &ContainerAttrs) -> syn::Result<()> {
+    let mut seen: Vec<(String, &Member)> = Vec::new();
+    for field in fields {
+        if field.attrs.flatten || field.is_skipped() || field.attrs.shared {
+            continue;
+        }
+        let name = resolve_env_var_name(field, container);
+        let aliases = field
+            .attrs
+            .aliases
+            .iter()
+            .map(|alias| format!("{}{}", container.prefix, alias))
+            .filter(|alias| *alias != name);
+        for name in std::iter::once(name.clone()).chain(aliases) {
+            if let Some((_, other)) = seen.iter().find(|(seen, _)| *seen == name) {
+                return Err(syn::Error::new_spanned(
+                    field.field,
+                    format!(
+                        "fields `{}` and `{}` both read the variable {}",
+                        describe_member(other),
+                        describe_member(&field.member),
+                        name
+                    ),
+                ));
+            }
+            seen.push((name, &field.member));
+        }
+    }
+    // A shared field is another view of a variable that some other field reads
+    for field in fields.iter().filter(|field| field.attrs.shared) {
+        let name = resolve_env_var_name(field, container);
+        if !seen.iter().any(|(seen, _)| *seen == name) {
+            return Err(syn::Error::new_spanned(
+                field.field,
+                format!(
+                    "field `{}` is `shared`, but no other field reads the variable {}",
+                    describe_member(&field.member),
+                    name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn describe_member(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.unraw().to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+// Generates a `Debug` implementation that prints secret fields as `***`
+fn generate_debug_impl(
+    name: &Ident,
+    generics: &Generics,
+    fields: &[EnvField],
+    is_tuple: bool,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name_str = name.to_string();
+    let debug_fields = fields.iter().map(|field| {
+        let member = &field.member;
+        let value = if field.attrs.secret {
+            quote! { &format_args!("***") }
+        } else {
+            quote! { &self.#member }
+        };
+        match &field.field.ident {
+            Some(ident) => {
+                let field_str = ident.to_string();
+                quote! { .field(#field_str, #value) }
+            }
+            None => quote! { .field(#value) },
+        }
+    });
+    let builder = if is_tuple {
+        quote! { debug_tuple }
+    } else {
+        quote! { debug_struct }
+    };
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.#builder(#name_str)
+                    #(#debug_fields)*
+                    .finish()
+            }
+        }
+    }
+}
+
+// Generates a `Display` implementation listing every variable as `NAME = value`, with secrets redacted
+fn generate_display_impl(
+    name: &Ident,
+    generics: &Generics,
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let lines = fields
+        .iter()
+        .filter(|field| !field.is_skipped() && !field.attrs.shared)
+        .map(|field| {
+            let member = &field.member;
+            if field.attrs.flatten {
+                return match flatten_prefix(field, container) {
+                    Some(prefix) => quote! {
+                        f.write_str(&::envar::__private::prefix_lines(#prefix, &::std::string::ToString::to_string(&self.#member)))?;
+                    },
+                    None => quote! { ::core::write!(f, "{}", self.#member)?; },
+                };
+            }
+            let env_var_name = resolve_env_var_name(field, container);
+            if field.attrs.secret {
+                quote! { ::core::writeln!(f, "{} = ***", #env_var_name)?; }
+            } else if field.attrs.lazy {
+                quote! {
+                    match self.#member.get() {
+                        ::core::option::Option::Some(value) => ::core::writeln!(f, "{} = {:?}", #env_var_name, value)?,
+                        ::core::option::Option::None => ::core::writeln!(f, "{} = <unread>", #env_var_name)?,
+                    }
+                }
+            } else {
+                quote! { ::core::writeln!(f, "{} = {:?}", #env_var_name, self.#member)?; }
+            }
+        });
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                #(#lines)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    }
+}
+
+// Generates `as_map()`, which writes the loaded values back out keyed by their variable names
+fn generate_as_map(fields: &[EnvField], container: &ContainerAttrs) -> proc_macro2::TokenStream {
+    let entries = fields
+        .iter()
+        // The variable of a `path_from` field is a path, which can't be recovered from the contents
+        .filter(|field| !field.is_skipped() && !field.attrs.shared && !field.attrs.path_from)
+        .map(|field| {
+            let (member, attrs, ty) = (&field.member, &field.attrs, &field.field.ty);
+            if attrs.flatten {
+                return match flatten_prefix(field, container) {
+                    Some(prefix) => quote! {
+                        __map.extend(self.#member.as_map().into_iter().map(|(var, value)| (::std::format!("{}{}", #prefix, var), value)));
+                    },
+                    None => quote! { __map.extend(self.#member.as_map()); },
+                };
+            }
+            let env_var_name = resolve_env_var_name(field, container);
+            if attrs.indexed_list {
+                let item_prefix = item_prefix(field, container);
+                return quote! {
+                    __map.insert(#env_var_name.to_string(), self.#member.len().to_string());
+                    for (index, item) in self.#member.iter().enumerate() {
+                        for (var, value) in item.as_map() {
+                            __map.insert(::std::format!("{}{}_{}", #item_prefix, index, var), value);
+                        }
+                    }
+                };
+            }
+            // A `Lazy` field is parsed to be written out, and left out if it can't be
+            if let Some(inner_ty) = extract_generic_inner_type(ty, "Lazy") {
+                let value = generate_env_value(inner_ty, attrs, quote! { value });
+                return quote! {
+                    if let ::core::result::Result::Ok(value) = self.#member.try_get() {
+                        __map.insert(#env_var_name.to_string(), #value);
+                    }
+                };
+            }
+            // A lazy field that wasn't read yet is left out, like an unset one
+            if let Some(inner_ty) = extract_generic_inner_type(ty, "OnceLock").filter(|_| attrs.lazy) {
+                let value = generate_env_value(inner_ty, attrs, quote! { value });
+                return quote! {
+                    if let ::core::option::Option::Some(value) = self.#member.get() {
+                        __map.insert(#env_var_name.to_string(), #value);
+                    }
+                };
+            }
+            // An unset flag and a `None` have no variable at all
+            if attrs.flag {
+                return quote! {
+                    if self.#member {
+                        __map.insert(#env_var_name.to_string(), "true".to_string());
+                    }
+                };
+            }
+            match extract_option_inner_type(ty) {
+                Some(inner_ty) => {
+                    let value = generate_env_value(inner_ty, attrs, quote! { value });
+                    quote! {
+                        if let ::core::option::Option::Some(value) = &self.#member {
+                            __map.insert(#env_var_name.to_string(), #value);
+                        }
+                    }
+                }
+                None => {
+                    let value = generate_env_value(ty, attrs, quote! { (&self.#member) });
+                    quote! { __map.insert(#env_var_name.to_string(), #value); }
+                }
+            }
+        });
+    quote! {
+        pub fn as_map(&self) -> ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+            let mut __map = ::std::collections::HashMap::new();
+            #(#entries)*
+            __map
+        }
+
+        pub fn env_diff(&self) -> ::std::vec::Vec<(::std::string::String, ::std::string::String, ::std::string::String)> {
+            let current = Self::collect().map(|config| config.as_map());
+            ::envar::__private::env_diff(self.as_map(), current)
+        }
+    }
+}
+
+// Generates an expression formatting the value behind the reference `value` as a `String`
+// that reads back as the same value where the type allows it
+fn generate_env_value(
+    ty: &Type,
+    attrs: &FieldAttrs,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let format_element = |ty: &Type| {
+        let element = generate_env_value(ty, &FieldAttrs::default(), quote! { element });
+        quote! { |element: &#ty| -> ::std::string::String { #element } }
+    };
+    if attrs.secret || extract_generic_inner_type(ty, "Secret").is_some() {
+        quote! { "***".to_string() }
+    } else if attrs.base64 && cfg!(feature = "base64") {
+        quote! { ::envar::__private::format_base64(#value) }
+    } else if attrs.json && cfg!(feature = "serde") {
+        quote! { ::envar::__private::format_json(#value) }
+    } else if attrs.default_unit.is_some() {
+        // A bare number would be read back in the default unit
+        quote! { ::std::format!("{}B", #value) }
+    } else if let Some(radix) = &attrs.radix {
+        let format = match radix.base10_digits() {
+            "16" => "{:#x}",
+            "8" => "{:#o}",
+            "2" => "{:#b}",
+            _ => "{}",
+        };
+        quote! { ::std::format!(#format, #value) }
+    } else if is_type_named(ty, "Duration") {
+        if attrs.secs {
+            quote! { #value.as_secs().to_string() }
+        } else if attrs.millis {
+            quote! { #value.as_millis().to_string() }
+        } else {
+            quote! { ::envar::__private::format_duration(#value) }
+        }
+    } else if let Some(inner_ty) = extract_collection_inner_type(ty).or(match ty {
+        Type::Array(array) => Some(&*array.elem),
+        _ => None,
+    }) {
+        let (delimiter, format_element) = (join_delimiter(attrs), format_element(inner_ty));
+        quote! { #value.iter().map(#format_element).collect::<::std::vec::Vec<::std::string::String>>().join(#delimiter) }
+    } else if let Some((key_ty, value_ty)) = extract_map_types(ty) {
+        let delimiter = join_delimiter(attrs);
+        let (format_key, format_value) = (format_element(key_ty), format_element(value_ty));
+        quote! {
+            #value
+                .iter()
+                .map(|(key, value)| ::std::format!("{}={}", (#format_key)(key), (#format_value)(value)))
+                .collect::<::std::vec::Vec<::std::string::String>>()
+                .join(#delimiter)
+        }
+    } else if is_type_named(ty, "PathBuf") {
+        quote! { #value.display().to_string() }
+    } else if is_type_named(ty, "OsString") {
+        quote! { #value.to_string_lossy().into_owned() }
+    } else {
+        quote! { ::std::string::ToString::to_string(#value) }
+    }
+}
+
+// Generates the `env_var_names()` function listing every variable the struct reads
+fn generate_env_var_names(
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+) -> proc_macro2::TokenStream {
+    let mut names = Vec::new();
+    let mut flattened = Vec::new();
+    for field in fields {
+        if field.is_skipped() || field.attrs.shared {
+            continue;
+        } else if field.attrs.flatten {
+            let ty = &field.field.ty;
+            flattened.push(match flatten_prefix(field, container) {
+                Some(prefix) => quote! {
+                    names.extend(<#ty>::env_var_names().into_iter().map(|name| ::envar::__private::prefix_name(#prefix, &name)));
+                },
+                None => quote! { names.extend(<#ty>::env_var_names()); },
+            });
+        } else {
+            names.push(resolve_env_var_name(field, container));
+        }
+    }
+    // The list is built on every call rather than cached in a `static`, which a generic impl
+    // would share across all of its instantiations
+    quote! {
+        pub fn env_var_names() -> ::std::vec::Vec<::std::borrow::Cow<'static, str>> {
+            #[allow(unused_mut)]
+            let mut names: ::std::vec::Vec<::std::borrow::Cow<'static, str>> =
+                ::std::vec![#(::std::borrow::Cow::Borrowed(#names)),*];
+            #(#flattened)*
+            names
+        }
+    }
+}
+
+// Generates the hidden `__envar_arg_names()` function pairing variables with their `env(arg = "...")` keys
+fn generate_arg_names(fields: &[EnvField], container: &ContainerAttrs) -> proc_macro2::TokenStream {
+    let mut names = Vec::new();
+    let mut flattened = Vec::new();
+    for field in fields {
+        if field.attrs.flatten {
+            let ty = &field.field.ty;
+            flattened.push(match flatten_prefix(field, container) {
+                Some(prefix) => quote! {
+                    names.extend(<#ty>::__envar_arg_names().into_iter().map(|(name, arg)| (::envar::__private::prefix_name(#prefix, &name), arg)));
+                },
+                None => quote! { names.extend(<#ty>::__envar_arg_names()); },
+            });
+        } else if let Some(arg) = field.attrs.arg.as_ref().filter(|_| !field.attrs.shared) {
+            let name = resolve_env_var_name(field, container);
+            names.push(quote! { (::std::borrow::Cow::Borrowed(#name), #arg) });
+        }
+    }
+    quote! {
+        #[doc(hidden)]
+        pub fn __envar_arg_names() -> ::std::vec::Vec<(::std::borrow::Cow<'static, str>, &'static str)> {
+            #[allow(unused_mut)]
+            let mut names: ::std::vec::Vec<(::std::borrow::Cow<'static, str>, &'static str)> =
+                ::std::vec![#(#names),*];
+            #(#flattened)*
+            names
+        }
+    }
+}
+
+// Generates the `example_env()` function producing a `.env` template for the struct
+fn generate_example_env(
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+) -> proc_macro2::TokenStream {
+    let lines = fields.iter().map(|field| {
+        let (attrs, ty) = (&field.attrs, &field.field.ty);
+        if attrs.flatten {
+            return match flatten_prefix(field, container) {
+                Some(prefix) => quote! {
+                    example.push_str(&::envar::__private::prefix_lines(#prefix, &<#ty>::example_env()));
+                },
+                None => quote! { example.push_str(&<#ty>::example_env()); },
+            };
+        }
+        if field.is_skipped() || attrs.shared {
+            return quote! {};
+        }
+        let mut entry = String::new();
+        if is_option_type(ty) {
+            entry.push_str("# Optional\n");
+        }
+        if let Some(default) = &attrs.default {
+            entry.push_str(&format!("# Default: {}\n", default));
+        } else if let Some(default_fn) = &attrs.default_fn {
+            entry.push_str(&format!(
+                "# Default: computed by {}\n",
+                quote! { #default_fn }
+            ));
+        } else if let Some(default_const) = &attrs.default_const {
+            entry.push_str(&format!("# Default: {}\n", quote! { #default_const }));
+        } else if let Some(default_expr) = &attrs.default_expr {
+            entry.push_str(&format!("# Default: {}\n", quote! { #default_expr }));
+        }
+        entry.push_str(&format!(
+            "{}={}\n",
+            resolve_env_var_name(field, container),
+            attrs.example.as_deref().unwrap_or("")
+        ));
+        quote! { example.push_str(#entry); }
+    });
+    quote! {
+        pub fn example_env() -> ::std::string::String {
+            let mut example = ::std::string::String::new();
+            #(#lines)*
+            example
+        }
+    }
+}
+
+fn generate_field_init(field: &EnvField, container: &ContainerAttrs) -> proc_macro2::TokenStream {
+    if field.attrs.lazy {
+        // Read by the field's accessor on first call instead
+        return quote! { ::core::result::Result::Ok(::std::sync::OnceLock::new()) };
+    }
+    let ty = &field.field.ty;
+    let read = match extract_generic_inner_type(ty, "Result") {
+        // The outcome is stored in the field, so reading a `Result` field never fails
+        Some(inner_ty) => {
+            let read = generate_field_read(field, inner_ty, container, false);
+            quote! { ::core::result::Result::Ok((#read).map_err(::envar::__private::FieldError::from_error)) }
+        }
+        None => match extract_generic_inner_type(ty, "Lazy") {
+            Some(inner_ty) => generate_field_read(field, inner_ty, container, true),
+            None => generate_field_read(field, ty, container, false),
+        },
+    };
+    match &field.attrs.requires {
+        // The field is left at its default without being read while the condition is off
+        Some(condition) => {
+            let condition = format!("{}{}", container.prefix, condition);
+            quote! {
+                if ::envar::__private::is_truthy(__source, #condition) {
+                    #read
+                } else {
+                    ::core::result::Result::Ok(::core::default::Default::default())
+                }
+            }
+        }
+        None => read,
+    }
+}
+
+// Rejects `env(lazy)` on fields that can't be read on their own by an accessor,
+// returning the type the field holds
+fn check_lazy<'a>(field: &'a EnvField) -> syn::Result<&'a Type> {
+    let inner_ty = extract_generic_inner_type(&field.field.ty, "OnceLock").ok_or_else(|| {
+        syn::Error::new_spanned(
+            &field.field.ty,
+            "`lazy` fields must be declared as `std::sync::OnceLock<T>`",
+        )
+    })?;
+    let attrs = &field.attrs;
+    if field.field.ident.is_none() {
+        return Err(syn::Error::new_spanned(
+            field.field,
+            "`lazy` needs a named field, to name its accessor after",
+        ));
+    }
+    if attrs.flatten
+        || attrs.flag
+        || attrs.indexed_list
+        || attrs.requires.is_some()
+        || attrs.default_expr.is_some()
+        || attrs.arg.is_some()
+    {
+        return Err(syn::Error::new_spanned(
+            field.field,
+            "`lazy` fields can't be used with `flatten`, `flag`, `indexed_list`, `requires`, `default_expr` or `arg`",
+        ));
+    }
+    Ok(inner_ty)
+}
+
+// Generates the accessors of `env(lazy)` fields, which read the value from the process environment,
+// and the `.env` file if there is one, the first time they are called
+fn generate_lazy_accessors(
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+) -> Vec<proc_macro2::TokenStream> {
+    let source = match &container.dotenv {
+        Some(path) => quote! {
+            let dotenv = ::envar::dotenv::read(#path).map_err(|source| ::envar::EnvarError::Dotenv { path: #path.to_string(), source })?;
+            let layered = ::envar::Layered::new().with(&::envar::StdEnv).with(&dotenv);
+            let __source = &layered;
+        },
+        None => quote! { let __source = &::envar::StdEnv; },
+    };
+    fields
+        .iter()
+        .filter(|field| field.attrs.lazy)
+        .filter_map(|field| {
+            let ident = field.field.ident.as_ref()?;
+            let inner_ty = extract_generic_inner_type(&field.field.ty, "OnceLock")?;
+            let try_ident = format_ident!("try_{}", ident.unraw());
+            let read = generate_field_read(field, inner_ty, container, false);
+            Some(quote! {
+                pub fn #ident(&self) -> &#inner_ty {
+                    match self.#try_ident() {
+                        ::core::result::Result::Ok(value) => value,
+                        ::core::result::Result::Err(err) => ::core::panic!("{}", err),
+                    }
+                }
+
+                pub fn #try_ident(&self) -> ::core::result::Result<&#inner_ty, ::envar::EnvarError> {
+                    if let ::core::option::Option::Some(value) = self.#ident.get() {
+                        return ::core::result::Result::Ok(value);
+                    }
+                    #source
+                    let value = (#read)?;
+                    ::core::result::Result::Ok(self.#ident.get_or_init(|| value))
+                }
+            })
+        })
+        .collect()
+}
+
+// With `lazy` the variable is looked up right away, but parsed into an `envar::Lazy` on first use
+fn generate_field_read(
+    field: &EnvField,
+    ty: &Type,
+    container: &ContainerAttrs,
+    lazy: bool,
+) -> proc_macro2::TokenStream {
+    let attrs = &field.attrs;
+    if lazy && (attrs.flatten || attrs.flag || attrs.required_if.is_some()) {
+        return syn::Error::new_spanned(
+            field.field,
+            "`Lazy` fields can't be used with `flatten`, `flag` or `required_if`",
+        )
+        .to_compile_error();
+    }
+
+    // Evaluates to a Result so that errors from every field can be collected
+    if attrs.flatten {
+        return match flatten_prefix(field, container) {
+            Some(prefix) => quote! {
+                <#ty>::__envar_collect(&::envar::Prefixed::new(#prefix, __source))
+                    .map_err(|errors| ::envar::EnvarError::from(::envar::__private::prefix_errors(#prefix, errors)))
+            },
+            None => quote! { <#ty>::from_source(__source) },
+        };
+    }
+    if field.is_skipped() {
+        return quote! { ::core::result::Result::Ok(::core::default::Default::default()) };
+    }
+
+    let env_var_name = resolve_env_var_name(field, container);
+    if attrs.indexed_list {
+        let item_ty = match extract_generic_inner_type(ty, "Vec") {
+            Some(item_ty) if !lazy => item_ty,
+            _ => {
+                return syn::Error::new_spanned(
+                    ty,
+                    "`indexed_list` can only be used on `Vec` fields",
+                )
+                .to_compile_error();
+            }
+        };
+        let item_prefix = item_prefix(field, container);
+        return quote! {
+            ::envar::__private::read_indexed(__source, #env_var_name, #item_prefix, |source| {
+                <#item_ty>::__envar_collect(source)
+            })
+        };
+    }
+    let aliases: Vec<_> = attrs
+        .aliases
+        .iter()
+        .map(|alias| format!("{}{}", container.prefix, alias))
+        .filter(|alias| *alias != env_var_name)
+        .collect();
+
+    let names = quote! { &[#env_var_name, #(#aliases),*] };
+    // Only whether the variable is set matters, so none of the value handling applies
+    if attrs.flag {
+        if !is_bool_type(ty) {
+            return syn::Error::new_spanned(ty, "`flag` can only be used on `bool` fields")
+                .to_compile_error();
+        }
+        let trace = generate_trace(container, &env_var_name, false);
+        return quote! {
+            ::envar::__private::Lookup::new(#names).get_os(__source).map(|found| {
+                #trace
+                found.is_some()
+            })
+        };
+    }
+    let lookup = generate_lookup(attrs, container, names);
+    if attrs.required_if.is_some() && !is_option_type(ty) {
+        return syn::Error::new_spanned(ty, "`required_if` can only be used on `Option` fields")
+            .to_compile_error();
+    }
+    if attrs.lenient && !is_option_type(ty) {
+        return syn::Error::new_spanned(ty, "`lenient` can only be used on `Option` fields")
+            .to_compile_error();
+    }
+    if !attrs.none_values.is_empty() && !is_option_type(ty) {
+        return syn::Error::new_spanned(ty, "`none_values` can only be used on `Option` fields")
+            .to_compile_error();
+    }
+    let value_ty = extract_option_inner_type(ty).unwrap_or(ty);
+    let lookup = if is_type_named(value_ty, "OsString") || is_type_named(value_ty, "PathBuf") {
+        // Read without a UTF-8 round trip, so values that aren't valid UTF-8 survive
+        quote! { #lookup.get_os(__source) }
+    } else {
+        quote! { #lookup.get(__source) }
+    };
+    // Errors of secret fields are masked both when the value is looked up and when it is parsed
+    let secret = attrs.secret || extract_generic_inner_type(value_ty, "Secret").is_some();
+    let lookup = if secret {
+        quote! { #lookup.map_err(|err| ::envar::__private::redact(err, ::core::option::Option::None)) }
+    } else {
+        lookup
+    };
+
+    let mut parse = generate_parse(value_ty, quote! { &var }, quote! { &val }, attrs);
+    if secret {
+        parse = quote! {
+            (#parse).map_err(|err| ::envar::__private::redact(err, ::core::option::Option::Some(::core::convert::AsRef::<::std::ffi::OsStr>::as_ref(&val))))
+        };
+    }
+    if let Some(message) = &attrs.deprecated {
+        // With aliases only the old names are deprecated, otherwise the variable itself is
+        let condition = if aliases.is_empty() {
+            quote! { true }
+        } else {
+            let file_var = format!("{}_FILE", env_var_name);
+            quote! { var != #env_var_name && var != #file_var }
+        };
+        parse = quote! {{
+            if #condition {
+                ::envar::__private::warn_deprecated(&var, #message);
+            }
+            #parse
+        }};
+    }
+    if lazy && attrs.default_expr.is_some() {
+        return syn::Error::new_spanned(
+            field.field,
+            "`Lazy` fields can't have a `default_expr`",
+        )
+        .to_compile_error();
+    }
+    let default = generate_default_value(value_ty, &env_var_name, attrs, &field.earlier);
+    match attrs.when_empty.as_deref() {
+        Some("none") if !is_option_type(ty) => {
+            return syn::Error::new_spanned(
+                ty,
+                "`when_empty = \"none\"` can only be used on `Option` fields",
+            )
+            .to_compile_error();
+        }
+        Some("default") if default.is_none() => {
+            return syn::Error::new_spanned(
+                field.field,
+                "`when_empty = \"default\"` needs a `default`, `default_fn`, `default_const` or `default_expr`",
+            )
+            .to_compile_error();
+        }
+        _ => {}
+    }
+    let trace = generate_trace(container, &env_var_name, default.is_some());
+    let (found, missing) = if is_option_type(ty) {
+        let mut missing = match default {
+            Some(default) => quote! { #default.map(::core::option::Option::Some) },
+            None => quote! { ::core::result::Result::Ok(::core::option::Option::None) },
+        };
+        if let Some(condition) = &attrs.required_if {
+            let condition = format!("{}{}", container.prefix, condition);
+            let reason = format!("is required because {} is true", condition);
+            missing = quote! {
+                if ::envar::__private::is_truthy(__source, #condition) {
+                    ::core::result::Result::Err(::envar::EnvarError::Invalid { var: #env_var_name.to_string(), reason: #reason.to_string() })
+                } else {
+                    #missing
+                }
+            };
+        }
+        let mut found = if attrs.lenient {
+            quote! { ::envar::__private::lenient(#parse) }
+        } else {
+            quote! { #parse.map(::core::option::Option::Some) }
+        };
+        if !attrs.none_values.is_empty() {
+            let none_values = &attrs.none_values;
+            found = quote! {
+                if ::envar::__private::is_none_value(&val, &[#(#none_values),*]) {
+                    ::core::result::Result::Ok(::core::option::Option::None)
+                } else {
+                    #found
+                }
+            };
+        }
+        if attrs.when_empty.as_deref() == Some("none") {
+            found = quote! {
+                if val.is_empty() {
+                    ::core::result::Result::Ok(::core::option::Option::None)
+                } else {
+                    #found
+                }
+            };
+        }
+        (found, missing)
+    } else {
+        let missing = default.unwrap_or_else(|| match &attrs.error {
+            Some(message) => quote! {
+                ::core::result::Result::Err(::envar::EnvarError::Custom { var: #env_var_name.to_string(), message: #message.to_string() })
+            },
+            None => quote! { ::core::result::Result::Err(::envar::EnvarError::Missing { var: #env_var_name.to_string() }) },
+        });
+        (parse, missing)
+    };
+    let found = if attrs.when_empty.as_deref() == Some("error") {
+        quote! {
+            if val.is_empty() {
+                ::core::result::Result::Err(::envar::EnvarError::Invalid { var: var.to_string(), reason: "must not be empty".to_string() })
+            } else {
+                #found
+            }
+        }
+    } else {
+        found
+    };
+    if lazy {
+        return quote! {
+            #lookup.map(|found| {
+                #trace
+                ::envar::Lazy::new(move || match found.clone() {
+                    ::core::option::Option::Some((var, val)) => #found,
+                    ::core::option::Option::None => #missing,
+                })
+            })
+        };
+    }
+    quote! {
+        #lookup.and_then(|found| {
+            #trace
+            match found {
+                ::core::option::Option::Some((var, val)) => #found,
+                ::core::option::Option::None => #missing,
+            }
+        })
+    }
+}
+
+// Generates the `envar(trace)` log of how a field's variable was resolved, given the `found` lookup result
+fn generate_trace(
+    container: &ContainerAttrs,
+    env_var_name: &str,
+    has_default: bool,
+) -> proc_macro2::TokenStream {
+    if !container.trace {
+        return quote! {};
+    }
+    quote! {
+        ::envar::__private::trace(#env_var_name, found.as_ref().map(|(var, _)| var.as_str()), #has_default);
+    }
+}
+
+// Generates the body of `__envar_collect` for `envar(serde)` structs, which gathers the raw values
+// of the fields and leaves parsing them to the struct's `Deserialize` implementation
+fn generate_serde_read(
+    fields: &[EnvField],
+    container: &ContainerAttrs,
+    is_tuple: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !cfg!(feature = "serde") {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "the `serde` feature of envar must be enabled to use `envar(serde)`",
+        ));
+    }
+    if let Some(field) = fields.first().filter(|_| is_tuple) {
+        return Err(syn::Error::new_spanned(
+            field.field,
+            "`envar(serde)` needs a struct with named fields",
+        ));
+    }
+    let mut reads = Vec::new();
+    let mut names = Vec::new();
+    for field in fields.iter().filter(|field| !field.is_skipped()) {
+        if field.attrs.flatten {
+            return Err(syn::Error::new_spanned(
+                field.field,
+                "`envar(flatten)` can't be used with `envar(serde)`, use `serde(flatten)` instead",
+            ));
+        }
+        if let Some(default_expr) = &field.attrs.default_expr {
+            return Err(syn::Error::new_spanned(
+                default_expr,
+                "`default_expr` can't be used with `envar(serde)`, use `serde(default)` instead",
+            ));
+        }
+        if field.attrs.lazy {
+            return Err(syn::Error::new_spanned(
+                field.field,
+                "`lazy` fields can't be used with `envar(serde)`",
+            ));
+        }
+        let key = field.local().unraw().to_string();
+        let env_var_name = resolve_env_var_name(field, container);
+        let aliases = field
+            .attrs
+            .aliases
+            .iter()
+            .map(|alias| format!("{}{}", container.prefix, alias))
+            .filter(|alias| *alias != env_var_name);
+        let lookup = generate_lookup(
+            &field.attrs,
+            container,
+            quote! { &[#env_var_name, #(#aliases),*] },
+        );
+        reads.push(quote! {
+            match #lookup.get(__source) {
+                ::core::result::Result::Ok(::core::option::Option::Some((var, value))) => __values.push((#key, var, value)),
+                ::core::result::Result::Ok(::core::option::Option::None) => {}
+                ::core::result::Result::Err(err) => __errors.push(err),
+            }
+        });
+        names.push(quote! { (#key, #env_var_name) });
+    }
+    Ok(quote! {
+        let mut __errors = ::std::vec::Vec::new();
+        let mut __values = ::std::vec::Vec::new();
+        #(#reads)*
+        if !__errors.is_empty() {
+            return ::core::result::Result::Err(__errors);
+        }
+        ::envar::__private::deserialize(&[#(#names),*], __values).map_err(|err| ::std::vec![err])
+    })
+}
+
+// Generates a `Lookup` of `names` that applies the value handling options of the field and container
+fn generate_lookup(
+    attrs: &FieldAttrs,
+    container: &ContainerAttrs,
+    names: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut lookup = quote! { ::envar::__private::Lookup::new(#names) };
+    if attrs.path_from {
+        lookup = quote! { #lookup.path_from() };
+    } else if attrs.file_fallback || container.file_fallback {
+        lookup = quote! { #lookup.file_fallback() };
+    }
+    if attrs.expand || container.expand {
+        lookup = quote! { #lookup.expand() };
+    }
+    if attrs.trim || container.trim_all {
+        lookup = quote! { #lookup.filter(::envar::__private::trim) };
+    }
+    if let Some(chars) = &attrs.trim_matches {
+        lookup = quote! {
+            #lookup.filter(|value: ::std::string::String| ::envar::__private::trim_matches(value, #chars))
+        };
+    }
+    // The field's own policy for empty values wins over the struct's
+    let empty_is_missing = match attrs.when_empty.as_deref() {
+        Some(policy) => policy == "default",
+        None => container.empty_is_missing,
+    };
+    if empty_is_missing {
+        lookup = quote! { #lookup.filter(::envar::__private::non_empty) };
+    }
+    if attrs.lowercase {
+        lookup = quote! { #lookup.filter(::envar::__private::lowercase) };
+    } else if attrs.uppercase {
+        lookup = quote! { #lookup.filter(::envar::__private::uppercase) };
+    }
+    if let Some(transform) = &attrs.transform {
+        let transform = typed_fn(
+            transform,
+            quote! { fn(::std::string::String) -> ::std::string::String },
+        );
+        lookup = quote! { #lookup.filter(|value: ::std::string::String| ::core::option::Option::Some((#transform)(value))) };
+    }
+    lookup
+}
+
+// The prefix a flattened field is read through, after the struct's own prefix, if it has one
+fn flatten_prefix(field: &EnvField, container: &ContainerAttrs) -> Option<String> {
+    let prefix = match field.attrs.flatten_prefix.as_ref()? {
+        Some(prefix) => prefix.clone(),
+        None => match &field.field.ident {
+            Some(ident) => format!("{}_", container.rename_all.apply(ident)),
+            None => String::new(),
+        },
+    };
+    Some(format!("{}{}", container.prefix, prefix))
+}
+
+// The prefix of the items of an `indexed_list` field, which is followed by the index and `_`
+fn item_prefix(field: &EnvField, container: &ContainerAttrs) -> String {
+    match &field.attrs.item_prefix {
+        Some(item_prefix) => format!("{}{}", container.prefix, item_prefix),
+        None => format!("{}_", resolve_env_var_name(field, container)),
+    }
+}
+
+// Binds the function at `path` with the signature `fn_ty`, so that a missing function or a wrong
+// signature is reported at the attribute naming it rather than somewhere in the generated code
+fn typed_fn(path: &syn::Path, fn_ty: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote_spanned! {path.span()=>
+        {
+            let function: #fn_ty = #path;
+            function
+        }
+    }
+}
+
+// Generates the `Result<ty, EnvarError>` used when the variable isn't set, if the field has a default.
+// A `default` string goes through the usual parse, while a `default_fn` is called for the value itself
+// and a `default_const` is the value itself. A `default_expr` sees the `earlier` fields by reference.
+fn generate_default_value(
+    ty: &Type,
+    env_var_name: &str,
+    attrs: &FieldAttrs,
+    earlier: &[Ident],
+) -> Option<proc_macro2::TokenStream> {
+    if let Some(default_expr) = &attrs.default_expr {
+        let value = quote_spanned! {default_expr.span()=>
+            ::core::result::Result::Ok::<#ty, ::envar::EnvarError>(#default_expr)
+        };
+        if earlier.is_empty() {
+            return Some(value);
+        }
+        return Some(quote! {
+            match (#(&#earlier,)*) {
+                #[allow(unused_variables)]
+                (#(::core::option::Option::Some(#earlier),)*) => #value,
+                // An earlier field failed to read and its error is already collected,
+                // so this one is left unset without adding another error
+                _ => ::core::result::Result::Err(::envar::EnvarError::Multiple(::std::vec::Vec::new())),
+            }
+        });
+    }
+    if let Some(default_fn) = &attrs.default_fn {
+        let default_fn = typed_fn(default_fn, quote! { fn() -> #ty });
+        return Some(
+            quote! { ::core::result::Result::Ok::<#ty, ::envar::EnvarError>((#default_fn)()) },
+        );
+    }
+    if let Some(default_const) = &attrs.default_const {
+        return Some(quote_spanned! {default_const.span()=>
+            ::core::result::Result::Ok::<#ty, ::envar::EnvarError>(#default_const)
+        });
+    }
+    let default = attrs.default.as_ref()?;
+    Some(generate_parse(
+        ty,
+        quote! { #env_var_name },
+        quote! { #default },
+        attrs,
+    ))
+}
+
+// Generates an expression that parses `value` into a `Result<ty, EnvarError>`
+fn generate_parse(
+    ty: &Type,
+    env_var_name: proc_macro2::TokenStream,
+    value: proc_macro2::TokenStream,
+    attrs: &FieldAttrs,
+) -> proc_macro2::TokenStream {
+    // The inner value is parsed and validated, then wrapped
+    if let Some(inner_ty) = extract_generic_inner_type(ty, "Secret") {
+        let parse = generate_parse(inner_ty, env_var_name, value, attrs);
+        return quote! { #parse.map(::envar::Secret::new) };
+    }
+
+    let parse = if let Some(parse_with) = &attrs.parse_with {
+        let parse_with = typed_fn(
+            parse_with,
+            quote! { fn(&str) -> ::core::result::Result<#ty, _> },
+        );
+        quote! { ::envar::__private::parse_with(#env_var_name, #value, #parse_with) }
+    } else if let Some(radix) = &attrs.radix {
+        quote! { ::envar::__private::parse_radix::<#ty>(#env_var_name, #value, #radix, <#ty>::from_str_radix) }
+    } else if attrs.json {
+        if !cfg!(feature = "serde") {
+            return syn::Error::new_spanned(
+                ty,
+                "the `serde` feature of envar must be enabled to use `json`",
+            )
+            .to_compile_error();
+        }
+        quote! { ::envar::__private::parse_json::<#ty>(#env_var_name, #value) }
+    } else if attrs.base64 {
+        if !cfg!(feature = "base64") {
+            return syn::Error::new_spanned(
+                ty,
+                "the `base64` feature of envar must be enabled to use `base64`",
+            )
+            .to_compile_error();
+        }
+        quote! { ::envar::__private::parse_base64(#env_var_name, #value) }
+    } else if attrs.count {
+        quote! { ::envar::__private::parse_count::<#ty>(#env_var_name, #value) }
+    } else if attrs.bytesize {
+        let default_unit = attrs.default_unit.as_deref().unwrap_or("");
+        quote! { ::envar::__private::parse_byte_size::<#ty>(#env_var_name, #value, #default_unit) }
+    } else if attrs.percent {
+        if !is_type_named(ty, "f32") && !is_type_named(ty, "f64") {
+            return syn::Error::new_spanned(
+                ty,
+                "`percent` can only be used on `f32` and `f64` fields",
+            )
+            .to_compile_error();
+        }
+        quote! { ::envar::__private::parse_percent::<#ty>(#env_var_name, #value) }
+    } else if let Some(inner_ty) = extract_collection_inner_type(ty) {
+        let delimiter = split_delimiter(attrs);
+        let parse_element = generate_parse_element(inner_ty);
+        quote! { ::envar::__private::parse_collection::<#ty, #inner_ty>(#env_var_name, #value, #delimiter, #parse_element) }
+    } else if let Type::Array(array) = ty {
+        let (delimiter, inner_ty, len) = (split_delimiter(attrs), &array.elem, &array.len);
+        let parse_element = generate_parse_element(inner_ty);
+        quote! { ::envar::__private::parse_array::<#inner_ty, { #len }>(#env_var_name, #value, #delimiter, #parse_element) }
+    } else if let Some((key_ty, value_ty)) = extract_map_types(ty) {
+        let delimiter = split_delimiter(attrs);
+        let (parse_key, parse_value) = (
+            generate_parse_element(key_ty),
+            generate_parse_element(value_ty),
+        );
+        quote! { ::envar::__private::parse_map::<#ty, #key_ty, #value_ty>(#env_var_name, #value, #delimiter, #parse_key, #parse_value) }
+    } else if is_bool_type(ty) {
+        quote! { ::envar::__private::parse_bool(#env_var_name, #value) }
+    } else if is_type_named(ty, "Duration") {
+        match (attrs.secs, attrs.millis) {
+            (true, true) => {
+                return syn::Error::new_spanned(ty, "`secs` and `millis` can't be used together")
+                    .to_compile_error();
+            }
+            (true, false) => quote! {
+                ::envar::__private::parse_duration_count(#env_var_name, #value, ::std::time::Duration::from_secs, "seconds")
+            },
+            (false, true) => quote! {
+                ::envar::__private::parse_duration_count(#env_var_name, #value, ::std::time::Duration::from_millis, "milliseconds")
+            },
+            (false, false) => quote! { ::envar::__private::parse_duration(#env_var_name, #value) },
+        }
+    } else if attrs.secs || attrs.millis {
+        return syn::Error::new_spanned(ty, "`secs` and `millis` only apply to `Duration` fields")
+            .to_compile_error();
+    } else if let Some(primitive) = non_zero_primitive(ty) {
+        let primitive_ty = Ident::new(primitive, proc_macro2::Span::call_site());
+        quote! {
+            ::envar::__private::parse_non_zero::<#primitive_ty, #ty>(#env_var_name, #value, <#ty>::new, #primitive)
+        }
+    } else if let Some(description) = describe_network_type(ty) {
+        quote! { ::envar::__private::parse_described::<#ty>(#env_var_name, #value, #description) }
+    } else if let Some(parse) = generate_chrono_parse(ty, &env_var_name, &value, attrs) {
+        parse
+    } else if let Some(format) = &attrs.format {
+        return syn::Error::new_spanned(
+            format,
+            "`format` only applies to date and time fields, with the `chrono`, `time` or `jiff` feature enabled",
+        )
+        .to_compile_error();
+    } else if cfg!(feature = "url") && is_type_named(ty, "Url") {
+        quote! { ::envar::__private::parse_url(#env_var_name, #value) }
+    } else if is_str_wrapper(ty, "Box") {
+        quote! { ::core::result::Result::Ok(::std::string::String::from(#value).into_boxed_str()) }
+    } else if is_str_wrapper(ty, "Cow") {
+        quote! { ::core::result::Result::Ok(::std::borrow::Cow::Owned(::std::string::String::from(#value))) }
+    } else if is_type_named(ty, "PathBuf") {
+        quote! { ::core::result::Result::Ok(::std::path::PathBuf::from(#value)) }
+    } else if is_type_named(ty, "OsString") {
+        quote! { ::core::result::Result::Ok(::std::ffi::OsString::from(#value)) }
+    } else if let Some(primitive) = integer_primitive(ty) {
+        quote! { ::envar::__private::parse_int::<#ty>(#env_var_name, #value, #primitive) }
+    } else {
+        quote! { ::envar::__private::parse::<#ty>(#env_var_name, #value) }
+    };
+    let validators = generate_validators(ty, &env_var_name, attrs);
+    quote! { #parse #(#validators)* }
+}
+
+// Generates the parse of a chrono, time or jiff date and time type, with the field's format or a default one
+fn generate_chrono_parse(
+    ty: &Type,
+    env_var_name: &proc_macro2::TokenStream,
+    value: &proc_macro2::TokenStream,
+    attrs: &FieldAttrs,
+) -> Option<proc_macro2::TokenStream> {
+    let format = attrs.format.as_ref().map(syn::LitStr::value);
+    let other_crates = [
+        (
+            cfg!(feature = "time"),
+            "OffsetDateTime",
+            "parse_offset_date_time",
+        ),
+        (cfg!(feature = "jiff"), "Timestamp", "parse_timestamp"),
+    ];
+    for (enabled, name, parse) in other_crates {
+        if enabled && is_type_named(ty, name) {
+            let parse = Ident::new(parse, proc_macro2::Span::call_site());
+            let format = match format {
+                Some(format) => quote! { ::core::option::Option::Some(#format) },
+                None => quote! { ::core::option::Option::None },
+            };
+            return Some(quote! { ::envar::__private::#parse(#env_var_name, #value, #format) });
+        }
+    }
+    if !cfg!(feature = "chrono") {
+        return None;
+    }
+    if extract_generic_inner_type(ty, "DateTime").is_some() {
+        let format = match format {
+            Some(format) => quote! { ::core::option::Option::Some(#format) },
+            None => quote! { ::core::option::Option::None },
+        };
+        return Some(quote! {
+            ::envar::__private::parse_date_time::<#ty>(#env_var_name, #value, #format)
+        });
+    }
+    let default_format = [
+        ("NaiveDate", "%Y-%m-%d"),
+        ("NaiveTime", "%H:%M:%S"),
+        ("NaiveDateTime", "%Y-%m-%dT%H:%M:%S"),
+    ]
+    .into_iter()
+    .find(|(name, _)| is_type_named(ty, name))
+    .map(|(_, format)| format)?;
+    let format = format.unwrap_or_else(|| default_format.to_string());
+    Some(quote! {
+        ::envar::__private::parse_naive::<#ty>(#env_var_name, #value, #format, <#ty>::parse_from_str)
+    })
+}
+
+// The delimiter argument of the collection parsers, where `None` splits on whitespace
+fn split_delimiter(attrs: &FieldAttrs) -> proc_macro2::TokenStream {
+    if attrs.split_whitespace {
+        return quote! { ::core::option::Option::None };
+    }
+    let delimiter = attrs.delimiter.as_deref().unwrap_or(",");
+    quote! { ::core::option::Option::Some(#delimiter) }
+}
+
+// The separator collections are written back out with
+fn join_delimiter(attrs: &FieldAttrs) -> &str {
+    if attrs.split_whitespace {
+        " "
+    } else {
+        attrs.delimiter.as_deref().unwrap_or(",")
+    }
+}
+
+// Generates a parser for the elements of a collection, so they are handled like fields of their own type
+fn generate_parse_element(ty: &Type) -> proc_macro2::TokenStream {
+    let parse = generate_parse(
+        ty,
+        quote! { var },
+        quote! { element },
+        &FieldAttrs::default(),
+    );
+    quote! { |var: &str, element: &str| #parse }
+}
+
+// Generates `.and_then(...)` checks run on the parsed value
+fn generate_validators(
+    ty: &Type,
+    env_var_name: &proc_macro2::TokenStream,
+    attrs: &FieldAttrs,
+) -> Vec<proc_macro2::TokenStream> {
+    let mut validators = Vec::new();
+    // `NaN` and `inf` parse as floats, but are rarely what a config means
+    if (is_type_named(ty, "f32") || is_type_named(ty, "f64")) && !attrs.allow_nonfinite {
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_finite(#env_var_name, value))
+        });
+    }
+    if attrs.percent && attrs.min.is_none() && attrs.max.is_none() {
+        // Without bounds of its own, a ratio is between 0% and 100%
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_range::<#ty>(#env_var_name, value, ::core::option::Option::Some(0.0), ::core::option::Option::Some(1.0)))
+        });
+    }
+    if attrs.min.is_some() || attrs.max.is_some() {
+        let bound = |bound: &Option<syn::Expr>| match bound {
+            Some(bound) => quote! { ::core::option::Option::Some(#bound) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let (min, max) = (bound(&attrs.min), bound(&attrs.max));
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_range::<#ty>(#env_var_name, value, #min, #max))
+        });
+    }
+    if attrs.min_len.is_some() || attrs.max_len.is_some() {
+        let bound = |bound: &Option<syn::Expr>| match bound {
+            Some(bound) => quote! { ::core::option::Option::Some(#bound) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let (min, max) = (bound(&attrs.min_len), bound(&attrs.max_len));
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_len(#env_var_name, value, #min, #max))
+        });
+    }
+    if let Some(pattern) = &attrs.pattern {
+        validators.push(generate_pattern_check(pattern, env_var_name));
+    }
+    if let Some(kind) = &attrs.must_exist {
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_exists(#env_var_name, value, #kind))
+        });
+    }
+    // Custom checks run last, on a value the built-in ones accepted
+    if let Some(validate) = &attrs.validate {
+        let validate = typed_fn(
+            validate,
+            quote! { fn(&#ty) -> ::core::result::Result<(), ::std::string::String> },
+        );
+        validators.push(quote! {
+            .and_then(|value| ::envar::__private::check_with(#env_var_name, value, #validate))
+        });
+    }
+    validators
+}
+
+#[cfg(feature = "regex")]
+fn generate_pattern_check(
+    pattern: &syn::LitStr,
+    env_var_name: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if let Err(err) = regex::Regex::new(&pattern.value()) {
+        return syn::Error::new_spanned(pattern, format!("invalid pattern: {}", err))
+            .to_compile_error();
+    }
+    // The regex is compiled once, the first time the field is read
+    quote! {
+        .and_then(|value| {
+            static PATTERN: ::std::sync::OnceLock<::envar::__private::Regex> = ::std::sync::OnceLock::new();
+            let pattern = PATTERN.get_or_init(|| ::envar::__private::Regex::new(#pattern).unwrap());
+            ::envar::__private::check_pattern(#env_var_name, value, pattern)
+        })
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+fn generate_pattern_check(
+    pattern: &syn::LitStr,
+    _env_var_name: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    syn::Error::new_spanned(
+        pattern,
+        "the `regex` feature of envar must be enabled to use `pattern`",
+    )
+    .to_compile_error()
+}
+
+// The element type of the collections read from a delimited list: `Vec`, `VecDeque`, `HashSet` and `BTreeSet`
+fn extract_collection_inner_type(ty: &Type) -> Option<&Type> {
+    ["Vec", "VecDeque", "HashSet", "BTreeSet"]
+        .into_iter()
+        .find_map(|collection| extract_generic_inner_type(ty, collection))
+}
+
+// The key and value types of the maps read from `key=value` entries: `HashMap` and `BTreeMap`
+fn extract_map_types(ty: &Type) -> Option<(&Type, &Type)> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "HashMap" || segment.ident == "BTreeMap" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    let mut types = args.args.iter().filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    });
+                    if let (Some(key_ty), Some(value_ty)) = (types.next(), types.next()) {
+                        return Some((key_ty, value_ty));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Helper function to check if a field is of type Option<T> and extract T if it is.
+fn extract_option_inner_type(ty: &Type) -> Option<&Type> {
+    extract_generic_inner_type(ty, "Option")
+}
+
+// The `T` of a `Wrapper<T>` named `wrapper`
+fn extract_generic_inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == wrapper {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    // Skip lifetimes, as in `Cow<'static, str>`
+                    return args.args.iter().find_map(|arg| match arg {
+                        GenericArgument::Type(inner_ty) => Some(inner_ty),
+                        _ => None,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+// Whether `ty` is `wrapper<str>`, like `Box<str>` or `Cow<'static, str>`
+fn is_str_wrapper(ty: &Type, wrapper: &str) -> bool {
+    extract_generic_inner_type(ty, wrapper).is_some_and(|inner_ty| is_type_named(inner_ty, "str"))
+}
+
+// The integer type wrapped by a `std::num::NonZero*` type
+fn non_zero_primitive(ty: &Type) -> Option<&'static str> {
+    [
+        ("NonZeroU8", "u8"),
+        ("NonZeroU16", "u16"),
+        ("NonZeroU32", "u32"),
+        ("NonZeroU64", "u64"),
+        ("NonZeroU128", "u128"),
+        ("NonZeroUsize", "usize"),
+        ("NonZeroI8", "i8"),
+        ("NonZeroI16", "i16"),
+        ("NonZeroI32", "i32"),
+        ("NonZeroI64", "i64"),
+        ("NonZeroI128", "i128"),
+        ("NonZeroIsize", "isize"),
+    ]
+    .into_iter()
+    .find(|(name, _)| is_type_named(ty, name))
+    .map(|(_, primitive)| primitive)
+}
+
+// Returns the name of a primitive integer type, for range errors
+fn integer_primitive(ty: &Type) -> Option<&'static str> {
+    [
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ]
+    .into_iter()
+    .find(|name| is_type_named(ty, name))
+}
+
+// Names the `std::net` address types for their parse errors
+fn describe_network_type(ty: &Type) -> Option<&'static str> {
+    [
+        ("SocketAddr", "socket address"),
+        ("SocketAddrV4", "IPv4 socket address"),
+        ("SocketAddrV6", "IPv6 socket address"),
+        ("IpAddr", "IP address"),
+        ("Ipv4Addr", "IPv4 address"),
+        ("Ipv6Addr", "IPv6 address"),
+    ]
+    .into_iter()
+    .find(|(name, _)| is_type_named(ty, name))
+    .map(|(_, description)| description)
+}
+
+// Matches a type by its last path segment, so `Duration`, `std::time::Duration` and
+// `core::time::Duration` are all recognized however they were imported
+fn is_type_named(ty: &Type, name: &str) -> bool {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == name && segment.arguments.is_empty();
+        }
+    }
+    false
+}
+
+fn is_phantom_data(ty: &Type) -> bool {
+    extract_generic_inner_type(ty, "PhantomData").is_some()
+}
+
+fn is_bool_type(ty: &Type) -> bool {
+    if let Type::Path(type_path) = ty {
+        return type_path.path.is_ident("bool");
+    }
+    false
+}
+
+fn is_option_type(ty: &syn::Type) -> bool {
+    // Simplified type check for Option<T>
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "Option";
+        }
+    }
+    false
+}
+
+/// Derives a case-insensitive `FromStr` for an enum of unit variants, so it can be read from a variable.
+///
+/// The value is matched against the variant names ignoring case,
+/// and anything else is an error listing the accepted values.
+/// ```rust
+/// use envar::{Envar, EnvarEnum};
+/// #[derive(EnvarEnum, Debug, PartialEq)]
+/// enum LogLevel {
+///     Error,
+///     Warn,
+///     Info,
+///     Debug,
+/// }
+///
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "ENUM_EXAMPLE_LOG_LEVEL"]
+///   log_level: LogLevel,
+///}
+/// # std::env::set_var("ENUM_EXAMPLE_LOG_LEVEL", "verbose");
+/// let err = Config::try_new().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "Failed to parse environment variable ENUM_EXAMPLE_LOG_LEVEL=\"verbose\": \
+///      expected one of error, warn, info, debug"
+/// );
+/// # std::env::set_var("ENUM_EXAMPLE_LOG_LEVEL", "INFO");
+/// let config = Config::new();
+/// assert_eq!(config.log_level, LogLevel::Info);
+/// ```
+///
+/// Variants with fields can't be selected by name and are rejected.
+/// ```compile_fail
+/// use envar::EnvarEnum;
+/// #[derive(EnvarEnum)]
+/// enum Backend {
+///     Memory,
+///     Postgres(String),
+/// }
+/// ```
+///
+/// So are variants whose names only differ in case, since no value could tell them apart.
+/// ```compile_fail
+/// use envar::EnvarEnum;
+/// #[derive(EnvarEnum)]
+/// enum Mode {
+///     Fast,
+///     FAST,
+/// }
+/// ```
+#[proc_macro_derive(EnvarEnum)]
+pub fn envar_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match enums::derive_envar_enum(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Reads a small config without declaring a struct for it.
+///
+/// Each `NAME: Type` entry reads the variable `NAME` into a field named after it in lowercase,
+/// parsed exactly like a field of a `#[derive(Envar)]` struct.
+/// An entry can end with `= default`, a string, number or boolean used when the variable isn't set.
+/// Number defaults can be negative.
+/// The macro evaluates to the result of `try_new()`, a `Result<_, envar::EnvarError>`.
+/// ```rust
+/// # std::env::set_var("CONFIG_EXAMPLE_PORT", "8080");
+/// let config = envar::config! {
+///     CONFIG_EXAMPLE_PORT: u16,
+///     CONFIG_EXAMPLE_HOST: String = "localhost",
+///     CONFIG_EXAMPLE_WORKERS: usize = 4,
+///     CONFIG_EXAMPLE_OFFSET: i32 = -1,
+/// }
+/// .unwrap();
+/// assert_eq!(config.config_example_port, 8080);
+/// assert_eq!(config.config_example_host, "localhost");
+/// assert_eq!(config.config_example_workers, 4);
+/// assert_eq!(config.config_example_offset, -1);
+/// ```
+///
+/// Missing and malformed variables are reported like they are for the derive.
+/// ```rust
+/// let result = envar::config! {
+///     CONFIG_EXAMPLE_UNSET_PORT: u16,
+/// };
+/// assert!(matches!(result, Err(envar::EnvarError::Missing { .. })));
+/// ```
+#[proc_macro]
+pub fn config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as config::ConfigInput);
+    match config::expand_config(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,1404 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::num::{IntErrorKind, ParseIntError};
+use std::ops::Div;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::{EnvSource, EnvarError, Prefixed};
+
+/// Finds the value of a field among the names it can be read from.
+pub struct Lookup<'a> {
+    names: &'a [&'static str],
+    file_fallback: bool,
+    path_from: bool,
+    expand: bool,
+    filters: Vec<fn(String) -> Option<String>>,
+}
+
+impl<'a> Lookup<'a> {
+    pub fn new(names: &'a [&'static str]) -> Self {
+        Lookup {
+            names,
+            file_fallback: false,
+            path_from: false,
+            expand: false,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Checks `<NAME>_FILE` before each name, reading the value from the file it points to.
+    pub fn file_fallback(mut self) -> Self {
+        self.file_fallback = true;
+        self
+    }
+
+    /// Treats the value of each name as the path of a file, and reads the trimmed contents of the file.
+    pub fn path_from(mut self) -> Self {
+        self.path_from = true;
+        self
+    }
+
+    /// Replaces `${OTHER}` and `${OTHER:-default}` references with values from the source.
+    pub fn expand(mut self) -> Self {
+        self.expand = true;
+        self
+    }
+
+    /// Runs every value through `filter`; a value that is filtered out counts as unset.
+    pub fn filter(mut self, filter: fn(String) -> Option<String>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Returns the first name that is set in `source`, along with its value.
+    pub fn get<S: EnvSource + ?Sized>(
+        &self,
+        source: &S,
+    ) -> Result<Option<(String, String)>, EnvarError> {
+        for name in self.names {
+            if let Some((var, value)) = self.get_raw(source, name)? {
+                let value = if self.expand {
+                    expand(source, &var, &value)?
+                } else {
+                    value
+                };
+                let value = self
+                    .filters
+                    .iter()
+                    .try_fold(value, |value, filter| filter(value));
+                if let Some(value) = value {
+                    return Ok(Some((var, value)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Like [`get`](Lookup::get), but reads the raw value without requiring it to be valid UTF-8.
+    ///
+    /// Processing a value needs it as a string, so with any processing this is [`get`](Lookup::get).
+    pub fn get_os<S: EnvSource + ?Sized>(
+        &self,
+        source: &S,
+    ) -> Result<Option<(String, OsString)>, EnvarError> {
+        if self.file_fallback || self.path_from || self.expand || !self.filters.is_empty() {
+            let found = self.get(source)?;
+            return Ok(found.map(|(var, value)| (var, OsString::from(value))));
+        }
+        Ok(self
+            .names
+            .iter()
+            .find_map(|name| source.get_os(name).map(|value| (name.to_string(), value))))
+    }
+
+    fn get_raw<S: EnvSource + ?Sized>(
+        &self,
+        source: &S,
+        name: &str,
+    ) -> Result<Option<(String, String)>, EnvarError> {
+        if self.path_from {
+            return match source.get_os(name) {
+                Some(path) => read_file(name.to_string(), path).map(Some),
+                None => Ok(None),
+            };
+        }
+        if self.file_fallback {
+            let file_var = format!("{}_FILE", name);
+            if let Some(path) = source.get_os(&file_var) {
+                return read_file(file_var, path).map(Some);
+            }
+        }
+        match source.get(name) {
+            Some(value) => Ok(Some((name.to_string(), value))),
+            // Set, but not as a string: reported rather than mistaken for a missing variable
+            None => match source.get_os(name) {
+                Some(value) => Err(EnvarError::NotUnicode {
+                    var: name.to_string(),
+                    value,
+                }),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+// Reads the trimmed contents of the file at `path`, which is the value of `var`
+fn read_file(var: String, path: OsString) -> Result<(String, String), EnvarError> {
+    match fs::read_to_string(&path) {
+        Ok(contents) => Ok((var, contents.trim().to_string())),
+        Err(source) => Err(EnvarError::File {
+            var,
+            path: path.to_string_lossy().into_owned(),
+            source,
+        }),
+    }
+}
+
+// Substitutes `${OTHER}` and `${OTHER:-default}` references in the value of `var`.
+// `$$` stands for a literal `$`.
+fn expand<S: EnvSource + ?Sized>(source: &S, var: &str, value: &str) -> Result<String, EnvarError> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$$") {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let Some(reference) = rest.strip_prefix("${") else {
+            expanded.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        let end = reference.find('}').ok_or_else(|| EnvarError::Parse {
+            var: var.to_string(),
+            value: value.to_string(),
+            source: "unterminated reference".into(),
+        })?;
+        let (name, default) = match reference[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&reference[..end], None),
+        };
+        match source.get(name).or_else(|| default.map(str::to_string)) {
+            Some(substitute) => expanded.push_str(&substitute),
+            None => {
+                return Err(EnvarError::Expand {
+                    var: var.to_string(),
+                    reference: name.to_string(),
+                })
+            }
+        }
+        rest = &reference[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// A value initialized once, backing the `global()` of `envar(singleton)` structs and [`crate::Lazy`] fields.
+///
+/// Unlike a bare `OnceLock`, a failed initialization stores nothing, so a later call can retry it,
+/// and initializations never race each other.
+pub struct Global<T> {
+    value: OnceLock<T>,
+    init: Mutex<()>,
+}
+
+impl<T> Default for Global<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Global<T> {
+    pub const fn new() -> Self {
+        Global {
+            value: OnceLock::new(),
+            init: Mutex::new(()),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    pub fn get_or_try_init(
+        &self,
+        init: impl FnOnce() -> Result<T, EnvarError>,
+    ) -> Result<&T, EnvarError> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let _guard = self.init.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = init()?;
+        Ok(self.value.get_or_init(|| value))
+    }
+}
+
+/// Command-line arguments seen as a source for `from_env_and_args()`.
+///
+/// Variables are looked up by their name, or by the `env(arg = "...")` key of their field in `names`.
+pub struct Args<'a> {
+    args: &'a HashMap<String, String>,
+    names: &'a [(Cow<'static, str>, &'static str)],
+}
+
+impl<'a> Args<'a> {
+    pub fn new(
+        args: &'a HashMap<String, String>,
+        names: &'a [(Cow<'static, str>, &'static str)],
+    ) -> Self {
+        Args { args, names }
+    }
+}
+
+impl EnvSource for Args<'_> {
+    fn get(&self, key: &str) -> Option<String> {
+        let key = match self.names.iter().find(|(var, _)| *var == key) {
+            Some((_, arg)) => arg,
+            None => key,
+        };
+        self.args.get(key).cloned()
+    }
+}
+
+/// The largest count an `envar(indexed_list)` field accepts.
+pub const MAX_INDEXED_ITEMS: usize = 1024;
+
+/// Reads the number of items from `count_var`, then every item with `read` through the prefix
+/// `<item_prefix><index>_`, for `envar(indexed_list)` fields.
+pub fn read_indexed<S, T>(
+    source: &S,
+    count_var: &str,
+    item_prefix: &str,
+    read: impl Fn(&Prefixed<'_, S>) -> Result<T, Vec<EnvarError>>,
+) -> Result<Vec<T>, EnvarError>
+where
+    S: EnvSource + ?Sized,
+{
+    let count: usize = match source.get(count_var) {
+        Some(count) => parse_int(count_var, count.trim(), "usize")?,
+        None => {
+            return Err(EnvarError::Missing {
+                var: count_var.to_string(),
+            })
+        }
+    };
+    if count > MAX_INDEXED_ITEMS {
+        return Err(EnvarError::Invalid {
+            var: count_var.to_string(),
+            reason: format!("must be at most {}, got {}", MAX_INDEXED_ITEMS, count),
+        });
+    }
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    for index in 0..count {
+        let prefix = format!("{}{}_", item_prefix, index);
+        match read(&Prefixed::new(&prefix, source)) {
+            Ok(item) => items.push(item),
+            Err(item_errors) => errors.extend(prefix_errors(&prefix, item_errors)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(items)
+    } else {
+        Err(errors.into())
+    }
+}
+
+/// The value `env_diff()` reports for a variable that no longer loads.
+pub const UNLOADABLE: &str = "<unloadable>";
+
+/// The variables whose value in `current` differs from the one in `loaded`, for `env_diff()`.
+///
+/// When the environment doesn't load anymore, the variables named by the errors are reported as
+/// [`UNLOADABLE`], or every variable if an error names none, such as a `.env` file that can't be read.
+pub fn env_diff(
+    loaded: HashMap<String, String>,
+    current: Result<HashMap<String, String>, Vec<EnvarError>>,
+) -> Vec<(String, String, String)> {
+    let current = match current {
+        Ok(current) => current,
+        Err(errors) => {
+            let mut names = Vec::new();
+            if !error_vars(&errors, &mut names) {
+                names = loaded.keys().cloned().collect();
+            }
+            names.sort();
+            names.dedup();
+            return names
+                .into_iter()
+                .map(|name| {
+                    let loaded = loaded.get(&name).cloned().unwrap_or_default();
+                    (name, loaded, UNLOADABLE.to_string())
+                })
+                .collect();
+        }
+    };
+    let mut names: Vec<&String> = loaded.keys().chain(current.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (loaded, current) = (
+                loaded.get(name).cloned().unwrap_or_default(),
+                current.get(name).cloned().unwrap_or_default(),
+            );
+            (loaded != current).then(|| (name.clone(), loaded, current))
+        })
+        .collect()
+}
+
+// Collects the variables `errors` are about into `names`, returning false if an error names none
+fn error_vars(errors: &[EnvarError], names: &mut Vec<String>) -> bool {
+    errors.iter().all(|error| match error {
+        EnvarError::Missing { var }
+        | EnvarError::Custom { var, .. }
+        | EnvarError::NotUnicode { var, .. }
+        | EnvarError::Parse { var, .. }
+        | EnvarError::Invalid { var, .. }
+        | EnvarError::Expand { var, .. }
+        | EnvarError::File { var, .. } => {
+            names.push(var.clone());
+            true
+        }
+        EnvarError::Multiple(errors) => error_vars(errors, names),
+        EnvarError::Dotenv { .. } => false,
+    })
+}
+
+/// Puts `prefix` in front of `name`, for the names of a flattened struct read through a prefix.
+pub fn prefix_name(prefix: &str, name: &str) -> Cow<'static, str> {
+    Cow::Owned(format!("{}{}", prefix, name))
+}
+
+/// Puts `prefix` in front of every `NAME=value` line of `text`, leaving comments and blank lines alone.
+pub fn prefix_lines(prefix: &str, text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() || line.starts_with('#') {
+                format!("{}\n", line)
+            } else {
+                format!("{}{}\n", prefix, line)
+            }
+        })
+        .collect()
+}
+
+/// Puts `prefix` in front of the variable names in `errors`, for configs read through a [`crate::Prefixed`] source.
+pub fn prefix_errors(prefix: &str, errors: Vec<EnvarError>) -> Vec<EnvarError> {
+    if prefix.is_empty() {
+        return errors;
+    }
+    errors
+        .into_iter()
+        .map(|mut error| {
+            match &mut error {
+                EnvarError::Missing { var }
+                | EnvarError::Custom { var, .. }
+                | EnvarError::NotUnicode { var, .. }
+                | EnvarError::Parse { var, .. }
+                | EnvarError::Invalid { var, .. }
+                | EnvarError::Expand { var, .. }
+                | EnvarError::File { var, .. } => var.insert_str(0, prefix),
+                EnvarError::Multiple(errors) => {
+                    *errors = prefix_errors(prefix, std::mem::take(errors));
+                }
+                EnvarError::Dotenv { .. } => {}
+            }
+            error
+        })
+        .collect()
+}
+
+/// The error types a `Result<T, E>` field can keep its outcome with.
+pub trait FieldError {
+    fn from_error(error: EnvarError) -> Self;
+}
+
+impl FieldError for EnvarError {
+    fn from_error(error: EnvarError) -> Self {
+        error
+    }
+}
+
+impl FieldError for String {
+    fn from_error(error: EnvarError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Warns that the value was read from the deprecated variable `var`.
+pub fn warn_deprecated(var: &str, message: &str) {
+    warn(&format!("{} is deprecated: {}", var, message));
+}
+
+/// Turns a value that failed to parse into `None`, with a warning, for `env(lenient)` fields.
+pub fn lenient<T>(result: Result<T, EnvarError>) -> Result<Option<T>, EnvarError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) => {
+            warn(&format!("{}, ignoring it", err));
+            Ok(None)
+        }
+    }
+}
+
+/// Logs how the variable `var` was resolved, for `envar(trace)` structs, without its value.
+///
+/// `found` is the variable that had the value, which can be an alias or a `_FILE` variable.
+#[cfg(feature = "log")]
+pub fn trace(var: &str, found: Option<&str>, has_default: bool) {
+    match found {
+        Some(found) if found == var => log::debug!("{} is set", var),
+        Some(found) => log::debug!("{} is set through {}", var, found),
+        None if has_default => log::debug!("{} is not set, using its default", var),
+        None => log::debug!("{} is not set", var),
+    }
+}
+
+fn warn(message: &str) {
+    #[cfg(feature = "log")]
+    log::warn!("{}", message);
+    #[cfg(not(feature = "log"))]
+    eprintln!("warning: {}", message);
+}
+
+/// Masks the value of a secret field wherever `error` shows it, keeping only its length.
+///
+/// `value` is the value that was parsed, once the lookup succeeded. The part of an `Invalid`
+/// reason naming what was received is dropped, as it can show the parsed value in another form.
+pub fn redact(mut error: EnvarError, value: Option<&OsStr>) -> EnvarError {
+    let value = value.map(OsStr::to_string_lossy);
+    let hide = |text: &str, secret: &str| {
+        if secret.is_empty() {
+            text.to_string()
+        } else {
+            text.replace(secret, &mask(secret))
+        }
+    };
+    match &mut error {
+        EnvarError::Parse {
+            value: parsed,
+            source,
+            ..
+        } => {
+            let mut message = hide(&source.to_string(), parsed);
+            if let Some(value) = &value {
+                message = hide(&message, value);
+            }
+            *source = message.into();
+            *parsed = mask(parsed);
+        }
+        EnvarError::Invalid { reason, .. } => {
+            if let Some(index) = reason.find(", got ") {
+                reason.truncate(index);
+            }
+            if let Some(value) = &value {
+                *reason = hide(reason, value);
+            }
+        }
+        EnvarError::NotUnicode { value, .. } => {
+            *value = OsString::from(mask(&value.to_string_lossy()));
+        }
+        EnvarError::Multiple(errors) => {
+            *errors = std::mem::take(errors)
+                .into_iter()
+                .map(|error| redact(error, value.as_deref().map(OsStr::new)))
+                .collect();
+        }
+        _ => {}
+    }
+    error
+}
+
+fn mask(value: &str) -> String {
+    "*".repeat(value.chars().count())
+}
+
+/// Trims a value, treating a blank value as unset.
+pub fn trim(value: String) -> Option<String> {
+    non_empty(value.trim().to_string())
+}
+
+/// Strips any of `chars` from both ends of a value.
+pub fn trim_matches(value: String, chars: &str) -> Option<String> {
+    Some(value.trim_matches(|c| chars.contains(c)).to_string())
+}
+
+/// Treats an empty value as unset.
+pub fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Converts a value to lowercase.
+pub fn lowercase(value: String) -> Option<String> {
+    Some(value.to_lowercase())
+}
+
+/// Converts a value to uppercase.
+pub fn uppercase(value: String) -> Option<String> {
+    Some(value.to_uppercase())
+}
+
+/// Whether `value` is one of the `none_values` of an optional field, ignoring case.
+pub fn is_none_value(value: impl AsRef<OsStr>, none_values: &[&str]) -> bool {
+    value.as_ref().to_str().is_some_and(|value| {
+        none_values
+            .iter()
+            .any(|none_value| value.eq_ignore_ascii_case(none_value))
+    })
+}
+
+/// Parses the value of `var` into `T`, mapping failures into [`EnvarError::Parse`].
+pub fn parse<T>(var: &str, value: &str) -> Result<T, EnvarError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn Error + Send + Sync>>,
+{
+    value.parse::<T>().map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: err.into(),
+    })
+}
+
+/// Parses the value of `var` into `T`, describing the expected kind of value on failure.
+pub fn parse_described<T: FromStr>(
+    var: &str,
+    value: &str,
+    description: &str,
+) -> Result<T, EnvarError> {
+    value.parse::<T>().map_err(|_| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!("not a valid {}", description).into(),
+    })
+}
+
+/// Parses the value of `var` as a primitive integer, reporting values that don't fit in the type.
+pub fn parse_int<T: FromStr<Err = ParseIntError>>(
+    var: &str,
+    value: &str,
+    description: &str,
+) -> Result<T, EnvarError> {
+    value.parse::<T>().map_err(|err| match err.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => EnvarError::Invalid {
+            var: var.to_string(),
+            reason: format!("value {} is out of range for {}", value, description),
+        },
+        _ => EnvarError::Parse {
+            var: var.to_string(),
+            value: value.to_string(),
+            source: err.into(),
+        },
+    })
+}
+
+/// Parses the value of `var` as an integer that must not be zero, such as `NonZeroU16`.
+pub fn parse_non_zero<T: FromStr, N>(
+    var: &str,
+    value: &str,
+    new: fn(T) -> Option<N>,
+    description: &str,
+) -> Result<N, EnvarError> {
+    value
+        .parse::<T>()
+        .ok()
+        .and_then(new)
+        .ok_or_else(|| EnvarError::Invalid {
+            var: var.to_string(),
+            reason: format!("must be a non-zero {}, got '{}'", description, value),
+        })
+}
+
+/// Parses the value of `var` as an integer in `radix`, with an optional `0x`, `0o` or `0b` prefix to match.
+pub fn parse_radix<T>(
+    var: &str,
+    value: &str,
+    radix: u32,
+    from_str_radix: fn(&str, u32) -> Result<T, ParseIntError>,
+) -> Result<T, EnvarError> {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value),
+    };
+    let prefix = match radix {
+        16 => Some(["0x", "0X"]),
+        8 => Some(["0o", "0O"]),
+        2 => Some(["0b", "0B"]),
+        _ => None,
+    };
+    let digits = prefix
+        .into_iter()
+        .flatten()
+        .find_map(|prefix| digits.strip_prefix(prefix))
+        .unwrap_or(digits);
+    from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!("not a valid base {} integer ({})", radix, err).into(),
+    })
+}
+
+/// Parses a ratio like `0.1`, or a percentage like `10%` which is divided by 100.
+pub fn parse_percent<T>(var: &str, value: &str) -> Result<T, EnvarError>
+where
+    T: FromStr + Div<Output = T> + From<u8>,
+{
+    let (number, percent) = match value.strip_suffix('%') {
+        Some(number) => (number.trim_end(), true),
+        None => (value, false),
+    };
+    let number: T = number.parse().map_err(|_| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: "expected a ratio like 0.1 or a percentage like 10%".into(),
+    })?;
+    Ok(if percent {
+        number / T::from(100)
+    } else {
+        number
+    })
+}
+
+/// Parses the value of `var` as a level, either a plain number or a repeated character like `vvv`.
+pub fn parse_count<T: TryFrom<usize>>(var: &str, value: &str) -> Result<T, EnvarError> {
+    let invalid = |reason: String| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: reason.into(),
+    };
+    let value = value.trim();
+    // A number too large to count is out of range, rather than a run of repeated digits
+    let count = if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        parse_int::<usize>(var, value, "usize")?
+    } else {
+        let mut chars = value.chars();
+        let first = chars.next();
+        if !chars.all(|c| Some(c) == first) {
+            return Err(invalid(
+                "expected a number or a repeated character such as vvv".to_string(),
+            ));
+        }
+        value.chars().count()
+    };
+    T::try_from(count).map_err(|_| {
+        invalid(format!(
+            "a count of {} doesn't fit in {}",
+            count,
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+/// Parses the value of `var` with a user supplied function, mapping failures into [`EnvarError::Parse`].
+pub fn parse_with<T, E>(
+    var: &str,
+    value: &str,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, EnvarError>
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    parse(value).map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: err.into(),
+    })
+}
+
+/// Parses the value of `var` as a boolean, accepting the common shell spellings.
+pub fn parse_bool(var: &str, value: &str) -> Result<bool, EnvarError> {
+    parse_with(var, value, |value| {
+        match value.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err("expected one of true/1/yes/on or false/0/no/off"),
+        }
+    })
+}
+
+/// Whether `var` is set in `source` to one of the `true` spellings accepted by [`parse_bool`].
+pub fn is_truthy<S: EnvSource + ?Sized>(source: &S, var: &str) -> bool {
+    source
+        .get(var)
+        .is_some_and(|value| matches!(parse_bool(var, value.trim()), Ok(true)))
+}
+
+/// Parses the value of `var` as a human readable duration such as `30s`, `5m` or `1h30m`.
+///
+/// The accepted units are `ns`, `us`, `ms`, `s`, `m`, `h` and `d`.
+pub fn parse_duration(var: &str, value: &str) -> Result<Duration, EnvarError> {
+    parse_with(var, value, duration_from_str)
+}
+
+/// Parses the value of `var` as a whole number of `unit`s, such as `30` seconds, and builds the duration with `from`.
+pub fn parse_duration_count(
+    var: &str,
+    value: &str,
+    from: fn(u64) -> Duration,
+    unit: &str,
+) -> Result<Duration, EnvarError> {
+    let invalid = |reason: String| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: reason.into(),
+    };
+    let value = value.trim();
+    if value.starts_with('-') {
+        return Err(invalid("a duration can't be negative".to_string()));
+    }
+    value
+        .parse::<u64>()
+        .map(from)
+        .map_err(|_| invalid(format!("expected a whole number of {}", unit)))
+}
+
+/// Formats a duration in the units [`parse_duration`] reads, such as `1m30s` or `250ms`.
+pub fn format_duration(value: &Duration) -> String {
+    let (secs, nanos) = (value.as_secs(), value.subsec_nanos());
+    let mut formatted = String::new();
+    if secs > 0 || nanos == 0 {
+        formatted.push_str(&format!("{}s", secs));
+    }
+    if nanos % 1_000_000 == 0 && nanos > 0 {
+        formatted.push_str(&format!("{}ms", nanos / 1_000_000));
+    } else if nanos > 0 {
+        formatted.push_str(&format!("{}ns", nanos));
+    }
+    formatted
+}
+
+fn duration_from_str(value: &str) -> Result<Duration, String> {
+    let invalid = || "expected a duration such as 10ms, 30s, 5m or 1h".to_string();
+    let too_large = || "duration is too large".to_string();
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    // Summed in nanoseconds, which holds any `Duration` without overflowing
+    let mut total: u128 = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount: u64 =
+            rest[..digits]
+                .parse()
+                .map_err(|err: ParseIntError| match err.kind() {
+                    IntErrorKind::PosOverflow => too_large(),
+                    _ => invalid(),
+                })?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let nanos_per_unit: u128 = match &rest[..unit_len] {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            "h" => 60 * 60 * 1_000_000_000,
+            "d" => 24 * 60 * 60 * 1_000_000_000,
+            _ => return Err(invalid()),
+        };
+        total = total
+            .checked_add(u128::from(amount) * nanos_per_unit)
+            .ok_or_else(too_large)?;
+        rest = rest[unit_len..].trim_start();
+    }
+    let secs = u64::try_from(total / 1_000_000_000).map_err(|_| too_large())?;
+    Ok(Duration::new(secs, (total % 1_000_000_000) as u32))
+}
+
+/// Parses the value of `var` as a number of bytes, such as `512`, `10MB` or `4KiB`.
+///
+/// `K`, `M`, `G` and `T` are powers of 1000 and `Ki`, `Mi`, `Gi` and `Ti` are powers of 1024,
+/// optionally followed by `B`. A bare number is in `default_unit`, the unit given with
+/// `env(default_unit)`, and is a count of bytes when `default_unit` is empty.
+pub fn parse_byte_size<T: TryFrom<u128>>(
+    var: &str,
+    value: &str,
+    default_unit: &str,
+) -> Result<T, EnvarError> {
+    let bytes = byte_size_from_str(value, default_unit).map_err(|reason| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: reason.into(),
+    })?;
+    T::try_from(bytes).map_err(|_| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!(
+            "{} bytes don't fit in {}",
+            bytes,
+            std::any::type_name::<T>()
+        )
+        .into(),
+    })
+}
+
+fn byte_size_from_str(value: &str, default_unit: &str) -> Result<u128, String> {
+    let invalid = || "expected a size such as 512, 64KB or 10MiB".to_string();
+    let value = value.trim();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let amount: u128 = value[..digits].parse().map_err(|_| invalid())?;
+    let unit = match value[digits..].trim_start() {
+        "" => default_unit,
+        unit => unit,
+    };
+    let multiplier = byte_unit(unit).ok_or_else(invalid)?;
+    amount.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+// The number of bytes in `unit`, which may be empty for plain bytes
+fn byte_unit(unit: &str) -> Option<u128> {
+    let unit = unit
+        .strip_suffix(['B', 'b'])
+        .unwrap_or(unit)
+        .to_ascii_lowercase();
+    Some(match unit.as_str() {
+        "" => 1,
+        "k" => 1000,
+        "m" => 1000_u128.pow(2),
+        "g" => 1000_u128.pow(3),
+        "t" => 1000_u128.pow(4),
+        "ki" => 1 << 10,
+        "mi" => 1 << 20,
+        "gi" => 1 << 30,
+        "ti" => 1 << 40,
+        _ => return None,
+    })
+}
+
+/// Checks that `value` lies within the inclusive `min` and `max` bounds.
+pub fn check_range<T>(var: &str, value: T, min: Option<T>, max: Option<T>) -> Result<T, EnvarError>
+where
+    T: PartialOrd + fmt::Display,
+{
+    let too_small = min.as_ref().is_some_and(|min| value < *min);
+    let too_large = max.as_ref().is_some_and(|max| value > *max);
+    if !too_small && !too_large {
+        return Ok(value);
+    }
+    let expected = describe_bounds(min, max);
+    Err(EnvarError::Invalid {
+        var: var.to_string(),
+        reason: format!("must be {}, got {}", expected, value),
+    })
+}
+
+/// Checks that a float is neither NaN nor infinite.
+pub fn check_finite<T>(var: &str, value: T) -> Result<T, EnvarError>
+where
+    T: Into<f64> + Copy + fmt::Display,
+{
+    if value.into().is_finite() {
+        Ok(value)
+    } else {
+        Err(EnvarError::Invalid {
+            var: var.to_string(),
+            reason: format!("must be a finite number, got {}", value),
+        })
+    }
+}
+
+/// Checks that the length of `value` in characters lies within the inclusive `min` and `max` bounds.
+pub fn check_len<T>(
+    var: &str,
+    value: T,
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Result<T, EnvarError>
+where
+    T: AsRef<str>,
+{
+    let len = value.as_ref().chars().count();
+    let too_short = min.is_some_and(|min| len < min);
+    let too_long = max.is_some_and(|max| len > max);
+    if !too_short && !too_long {
+        return Ok(value);
+    }
+    let expected = describe_bounds(min, max);
+    Err(EnvarError::Invalid {
+        var: var.to_string(),
+        reason: format!("must be {} characters long, got {}", expected, len),
+    })
+}
+
+/// Runs the `env(validate)` function on the parsed value of `var`, reporting its reason as invalid.
+pub fn check_with<T>(
+    var: &str,
+    value: T,
+    validate: fn(&T) -> Result<(), String>,
+) -> Result<T, EnvarError> {
+    match validate(&value) {
+        Ok(()) => Ok(value),
+        Err(reason) => Err(EnvarError::Invalid {
+            var: var.to_string(),
+            reason,
+        }),
+    }
+}
+
+/// Checks that the path in `value` exists, and is a directory or a file if `kind` is `dir` or `file`.
+pub fn check_exists<T: AsRef<Path>>(var: &str, value: T, kind: &str) -> Result<T, EnvarError> {
+    let path = value.as_ref();
+    let problem = match fs::metadata(path) {
+        Err(_) => "does not exist",
+        Ok(metadata) if kind == "dir" && !metadata.is_dir() => "is not a directory",
+        Ok(metadata) if kind == "file" && !metadata.is_file() => "is not a file",
+        Ok(_) => return Ok(value),
+    };
+    Err(EnvarError::Invalid {
+        var: var.to_string(),
+        reason: format!("points to {}, which {}", path.display(), problem),
+    })
+}
+
+fn describe_bounds<T: fmt::Display>(min: Option<T>, max: Option<T>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("between {} and {}", min, max),
+        (Some(min), None) => format!("at least {}", min),
+        (None, Some(max)) => format!("at most {}", max),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+pub use de::deserialize;
+
+#[cfg(feature = "regex")]
+pub use regex::Regex;
+
+/// Checks that `value` matches `pattern`.
+#[cfg(feature = "regex")]
+pub fn check_pattern<T>(var: &str, value: T, pattern: &Regex) -> Result<T, EnvarError>
+where
+    T: AsRef<str>,
+{
+    if pattern.is_match(value.as_ref()) {
+        Ok(value)
+    } else {
+        Err(EnvarError::Invalid {
+            var: var.to_string(),
+            reason: format!("must match the pattern {}", pattern.as_str()),
+        })
+    }
+}
+
+/// Decodes the value of `var` from standard, padded base64.
+#[cfg(feature = "base64")]
+pub fn parse_base64(var: &str, value: &str) -> Result<Vec<u8>, EnvarError> {
+    use base64::Engine;
+    parse_with(var, value, |value| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|err| format!("not valid base64 ({})", err))
+    })
+}
+
+/// Encodes bytes as standard, padded base64, the inverse of [`parse_base64`].
+#[cfg(feature = "base64")]
+pub fn format_base64(value: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(value)
+}
+
+/// Deserializes the value of `var` from JSON.
+#[cfg(feature = "serde")]
+pub fn parse_json<T: serde::de::DeserializeOwned>(var: &str, value: &str) -> Result<T, EnvarError> {
+    parse_with(var, value, |value| serde_json::from_str(value))
+}
+
+/// Serializes a value to JSON, the inverse of [`parse_json`].
+#[cfg(feature = "serde")]
+pub fn format_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Parses the value of `var` as a URL, explaining why a malformed one was rejected.
+#[cfg(feature = "url")]
+pub fn parse_url(var: &str, value: &str) -> Result<url::Url, EnvarError> {
+    parse_with(var, value, |value| {
+        url::Url::parse(value).map_err(|err| format!("not a valid URL ({})", err))
+    })
+}
+
+/// Parses the value of `var` as a date and time with an offset, in RFC 3339 unless a `format` is given.
+///
+/// The result can be any `DateTime` that converts from `DateTime<FixedOffset>`, such as `DateTime<Utc>`.
+#[cfg(feature = "chrono")]
+pub fn parse_date_time<T>(var: &str, value: &str, format: Option<&str>) -> Result<T, EnvarError>
+where
+    T: From<chrono::DateTime<chrono::FixedOffset>>,
+{
+    let parsed = match format {
+        Some(format) => chrono::DateTime::parse_from_str(value, format),
+        None => chrono::DateTime::parse_from_rfc3339(value),
+    };
+    parsed.map(T::from).map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!(
+            "expected a date and time in the format {} ({})",
+            format.unwrap_or("RFC 3339"),
+            err
+        )
+        .into(),
+    })
+}
+
+/// Parses the value of `var` as a `time::OffsetDateTime`, in RFC 3339 unless a `format` is given.
+///
+/// The format uses the `time` crate's format description syntax, like `[year]-[month]-[day]`.
+#[cfg(feature = "time")]
+pub fn parse_offset_date_time(
+    var: &str,
+    value: &str,
+    format: Option<&str>,
+) -> Result<time::OffsetDateTime, EnvarError> {
+    let parsed = match format {
+        Some(format) => time::format_description::parse_borrowed::<1>(format)
+            .map_err(|err| format!("invalid format {} ({})", format, err))
+            .and_then(|items| {
+                time::OffsetDateTime::parse(value, &items).map_err(|err| err.to_string())
+            }),
+        None => time::OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+            .map_err(|err| err.to_string()),
+    };
+    parsed.map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!(
+            "expected a date and time in the format {} ({})",
+            format.unwrap_or("RFC 3339"),
+            err
+        )
+        .into(),
+    })
+}
+
+/// Parses the value of `var` as a `jiff::Timestamp`, in RFC 3339 unless a `format` is given.
+///
+/// The format is a `strptime` format, like `%Y-%m-%d %H:%M %z`, and must include an offset.
+#[cfg(feature = "jiff")]
+pub fn parse_timestamp(
+    var: &str,
+    value: &str,
+    format: Option<&str>,
+) -> Result<jiff::Timestamp, EnvarError> {
+    let parsed = match format {
+        Some(format) => jiff::Timestamp::strptime(format, value),
+        None => value.parse(),
+    };
+    parsed.map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!(
+            "expected a timestamp in the format {} ({})",
+            format.unwrap_or("RFC 3339"),
+            err
+        )
+        .into(),
+    })
+}
+
+/// Parses the value of `var` as a naive chrono type with `parse`, which is given the `format`.
+#[cfg(feature = "chrono")]
+pub fn parse_naive<T>(
+    var: &str,
+    value: &str,
+    format: &str,
+    parse: fn(&str, &str) -> chrono::ParseResult<T>,
+) -> Result<T, EnvarError> {
+    parse(value, format).map_err(|err| EnvarError::Parse {
+        var: var.to_string(),
+        value: value.to_string(),
+        source: format!("expected the format {} ({})", format, err).into(),
+    })
+}
+
+/// A parser for a single element of a collection, given the variable name and the element.
+pub type ParseElement<T> = fn(&str, &str) -> Result<T, EnvarError>;
+
+/// Splits the value of `var` on `delimiter` and parses every element with `parse`.
+///
+/// Elements are trimmed, and an empty value yields an empty collection.
+/// Without a delimiter, the value is split on runs of whitespace.
+pub fn parse_collection<C, T>(
+    var: &str,
+    value: &str,
+    delimiter: Option<&str>,
+    parse: ParseElement<T>,
+) -> Result<C, EnvarError>
+where
+    C: FromIterator<T>,
+{
+    split(value, delimiter)
+        .map(|element| parse(var, element))
+        .collect()
+}
+
+/// Splits the value of `var` on `delimiter` into exactly `N` elements, parsing each one with `parse`.
+pub fn parse_array<T, const N: usize>(
+    var: &str,
+    value: &str,
+    delimiter: Option<&str>,
+    parse: ParseElement<T>,
+) -> Result<[T; N], EnvarError> {
+    let elements: Vec<T> = parse_collection(var, value, delimiter, parse)?;
+    elements
+        .try_into()
+        .map_err(|elements: Vec<T>| EnvarError::Invalid {
+            var: var.to_string(),
+            reason: format!("expected {} values, got {}", N, elements.len()),
+        })
+}
+
+/// Splits the value of `var` into `key=value` entries and parses both sides of each one.
+///
+/// Entries are separated by `delimiter`, and an empty value yields an empty map.
+/// A key that appears more than once is an error.
+pub fn parse_map<C, K, V>(
+    var: &str,
+    value: &str,
+    delimiter: Option<&str>,
+    parse_key: ParseElement<K>,
+    parse_value: ParseElement<V>,
+) -> Result<C, EnvarError>
+where
+    C: FromIterator<(K, V)>,
+{
+    let mut seen = HashSet::new();
+    split(value, delimiter)
+        .map(|entry| {
+            let invalid = |reason: String| EnvarError::Parse {
+                var: var.to_string(),
+                value: entry.to_string(),
+                source: reason.into(),
+            };
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| invalid("expected a key=value entry".to_string()))?;
+            let key = key.trim();
+            if !seen.insert(key) {
+                return Err(invalid(format!("duplicate key '{}'", key)));
+            }
+            Ok((parse_key(var, key)?, parse_value(var, value.trim())?))
+        })
+        .collect()
+}
+
+fn split<'a>(value: &'a str, delimiter: Option<&'a str>) -> impl Iterator<Item = &'a str> {
+    let value = value.trim();
+    let (elements, words) = match delimiter {
+        _ if value.is_empty() => (None, None),
+        Some(delimiter) => (Some(value.split(delimiter).map(str::trim)), None),
+        None => (None, Some(value.split_whitespace())),
+    };
+    elements
+        .into_iter()
+        .flatten()
+        .chain(words.into_iter().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn count_reads_numbers_and_repeated_characters() {
+        assert_eq!(parse_count::<u8>("V", "vvv").unwrap(), 3);
+        assert_eq!(parse_count::<u8>("V", " 2 ").unwrap(), 2);
+        assert_eq!(parse_count::<u8>("V", "").unwrap(), 0);
+        assert_eq!(parse_count::<u8>("V", "11").unwrap(), 11);
+    }
+
+    #[test]
+    fn count_rejects_mixed_characters() {
+        let err = parse_count::<u8>("V", "vvx").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse environment variable V=\"vvx\": expected a number or a repeated character such as vvv"
+        );
+    }
+
+    #[test]
+    fn count_reports_overflow_as_out_of_range() {
+        let err = parse_count::<u8>("V", "300").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse environment variable V=\"300\": a count of 300 doesn't fit in u8"
+        );
+        let err = parse_count::<u8>("V", "99999999999999999999999").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "V value 99999999999999999999999 is out of range for usize"
+        );
+    }
+
+    #[test]
+    fn redact_masks_parsed_values() {
+        let err = parse::<u32>("PIN", "12x4").unwrap_err();
+        let err = redact(err, Some(OsStr::new("12x4")));
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse environment variable PIN=\"****\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn redact_drops_received_values_from_validation_errors() {
+        let err = check_range("PIN", 1234, Some(5000), None).unwrap_err();
+        assert_eq!(err.to_string(), "PIN must be at least 5000, got 1234");
+        let err = redact(err, Some(OsStr::new("1234")));
+        assert_eq!(err.to_string(), "PIN must be at least 5000");
+    }
+
+    #[test]
+    fn redact_masks_values_that_are_not_unicode() {
+        let err = EnvarError::NotUnicode {
+            var: "PIN".to_string(),
+            value: OsString::from("1234"),
+        };
+        assert_eq!(
+            redact(err, None).to_string(),
+            "Environment variable PIN is not valid UTF-8: \"****\""
+        );
+    }
+
+    #[test]
+    fn redact_masks_every_error_of_a_list() {
+        let errors = vec![
+            parse::<u32>("PIN", "12x4").unwrap_err(),
+            parse::<u32>("PIN", "1.5").unwrap_err(),
+        ];
+        let EnvarError::Multiple(errors) = redact(EnvarError::Multiple(errors), None) else {
+            panic!("expected several errors");
+        };
+        for err in errors {
+            let EnvarError::Parse { value, .. } = err else {
+                panic!("expected a parse error");
+            };
+            assert!(value.chars().all(|c| c == '*'), "{}", value);
+        }
+    }
+
+    #[test]
+    fn env_diff_reports_changed_and_unset_variables() {
+        let loaded = map(&[("HOST", "localhost"), ("PORT", "8080")]);
+        assert!(env_diff(loaded.clone(), Ok(loaded.clone())).is_empty());
+        let current = map(&[("PORT", "9090"), ("WORKERS", "4")]);
+        assert_eq!(
+            env_diff(loaded, Ok(current)),
+            [
+                ("HOST".to_string(), "localhost".to_string(), String::new()),
+                ("PORT".to_string(), "8080".to_string(), "9090".to_string()),
+                ("WORKERS".to_string(), String::new(), "4".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_diff_reports_failing_variables_as_unloadable() {
+        let loaded = map(&[("HOST", "localhost"), ("PORT", "8080")]);
+        let errors = vec![parse::<u16>("PORT", "http").unwrap_err()];
+        assert_eq!(
+            env_diff(loaded, Err(errors)),
+            [(
+                "PORT".to_string(),
+                "8080".to_string(),
+                UNLOADABLE.to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn env_diff_reports_every_variable_when_no_error_names_one() {
+        let loaded = map(&[("HOST", "localhost"), ("PORT", "8080")]);
+        let errors = vec![EnvarError::Dotenv {
+            path: ".env".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, "line 1"),
+        }];
+        let diff = env_diff(loaded, Err(errors));
+        let names: Vec<&str> = diff.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(names, ["HOST", "PORT"]);
+        assert!(diff.iter().all(|(.., current)| current == UNLOADABLE));
+    }
+
+    #[test]
+    fn expand_substitutes_references() {
+        let source = map(&[("HOST", "db.internal")]);
+        assert_eq!(
+            expand(&source, "URL", "postgres://${HOST}:${PORT:-5432}/app").unwrap(),
+            "postgres://db.internal:5432/app"
+        );
+        assert_eq!(expand(&source, "PRICE", "$$5 or $6").unwrap(), "$5 or $6");
+    }
+
+    #[test]
+    fn expand_reports_unset_and_unterminated_references() {
+        let source = map(&[]);
+        assert_eq!(
+            expand(&source, "URL", "http://${HOST}/")
+                .unwrap_err()
+                .to_string(),
+            "Environment variable URL references HOST, which is not set"
+        );
+        assert_eq!(
+            expand(&source, "URL", "http://${HOST")
+                .unwrap_err()
+                .to_string(),
+            "Failed to parse environment variable URL=\"http://${HOST\": unterminated reference"
+        );
+    }
+
+    #[test]
+    fn duration_combines_units() {
+        assert_eq!(duration_from_str("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(
+            duration_from_str("1s 500ms"),
+            Ok(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn duration_accepts_amounts_beyond_u32() {
+        assert_eq!(
+            duration_from_str("5000000000ns"),
+            Ok(Duration::from_secs(5))
+        );
+        assert_eq!(
+            duration_from_str("18446744073709551615s"),
+            Ok(Duration::from_secs(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn duration_reports_overflow() {
+        let too_large = Err("duration is too large".to_string());
+        assert_eq!(duration_from_str("18446744073709551616s"), too_large);
+        assert_eq!(duration_from_str("18446744073709551615d"), too_large);
+        assert_eq!(duration_from_str("18446744073709551615s 1s"), too_large);
+    }
+
+    #[test]
+    fn duration_rejects_malformed_values() {
+        for value in ["", "10", "ms", "10 parsecs", "-5s", "1.5s"] {
+            assert!(
+                duration_from_str(value).is_err(),
+                "{:?} was accepted",
+                value
+            );
+        }
+    }
+}
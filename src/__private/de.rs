@@ -0,0 +1,229 @@
+use std::fmt;
+
+use serde::de::value::SeqDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+
+use crate::EnvarError;
+
+/// Deserializes `T` from the raw `values` of its fields, for `envar(serde)` structs.
+///
+/// Each value is a `(field, var, value)` triple, and `fields` maps every field to the variable it is
+/// read from, to name the variable when serde reports a missing field.
+pub fn deserialize<T: DeserializeOwned>(
+    fields: &[(&'static str, &'static str)],
+    values: Vec<(&'static str, String, String)>,
+) -> Result<T, EnvarError> {
+    let map = Map {
+        values: values.into_iter(),
+        var: None,
+    };
+    T::deserialize(map).map_err(|err| match err {
+        Error::Missing(field) => {
+            let var = fields
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map_or(field, |(_, var)| *var);
+            EnvarError::Missing {
+                var: var.to_string(),
+            }
+        }
+        Error::Parse { var, message } => {
+            let (var, value) = var.unwrap_or_default();
+            EnvarError::Parse {
+                var,
+                value,
+                source: message.into(),
+            }
+        }
+    })
+}
+
+#[derive(Debug)]
+enum Error {
+    Missing(&'static str),
+    Parse {
+        // The variable and its value, once the error is traced back to a field
+        var: Option<(String, String)>,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Missing(field) => write!(f, "missing field `{}`", field),
+            Error::Parse { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        Error::Parse {
+            var: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::Missing(field)
+    }
+}
+
+// The fields that were found, as a map from field name to value
+struct Map {
+    values: std::vec::IntoIter<(&'static str, String, String)>,
+    var: Option<(String, String)>,
+}
+
+impl<'de> de::Deserializer<'de> for Map {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for Map {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.values.next() {
+            Some((field, var, value)) => {
+                self.var = Some((var, value));
+                seed.deserialize(field.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (var, value) = self.var.take().expect("value requested before its key");
+        seed.deserialize(Value(&value)).map_err(|err| match err {
+            Error::Parse { var: None, message } => Error::Parse {
+                var: Some((var, value.clone())),
+                message,
+            },
+            err => err,
+        })
+    }
+}
+
+// A raw value, parsed into whatever type serde asks for
+struct Value<'a>(&'a str);
+
+impl Value<'_> {
+    fn parse<T: std::str::FromStr>(&self, expected: &str) -> Result<T, Error> {
+        self.0
+            .trim()
+            .parse()
+            .map_err(|_| de::Error::custom(format!("expected {}", expected)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident, $expected:literal;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse($expected)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match super::parse_bool("", self.0.trim()) {
+            Ok(value) => visitor.visit_bool(value),
+            Err(EnvarError::Parse { source, .. }) => Err(de::Error::custom(source)),
+            Err(err) => Err(de::Error::custom(err)),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8, "an integer";
+        deserialize_i16 => visit_i16, "an integer";
+        deserialize_i32 => visit_i32, "an integer";
+        deserialize_i64 => visit_i64, "an integer";
+        deserialize_i128 => visit_i128, "an integer";
+        deserialize_u8 => visit_u8, "a non-negative integer";
+        deserialize_u16 => visit_u16, "a non-negative integer";
+        deserialize_u32 => visit_u32, "a non-negative integer";
+        deserialize_u64 => visit_u64, "a non-negative integer";
+        deserialize_u128 => visit_u128, "a non-negative integer";
+        deserialize_f32 => visit_f32, "a number";
+        deserialize_f64 => visit_f64, "a number";
+        deserialize_char => visit_char, "a single character";
+    }
+
+    // A variable that is set holds a value, so `None` only comes from a missing field
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Lists are comma separated, like `Vec` fields of non-serde structs
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = self.0.trim();
+        let elements = if value.is_empty() {
+            Vec::new()
+        } else {
+            value
+                .split(',')
+                .map(|element| Value(element.trim()))
+                .collect()
+        };
+        visitor.visit_seq(SeqDeserializer::new(elements.into_iter()))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_enum(
+            self.0.trim().into_deserializer(),
+            name,
+            variants,
+            visitor,
+        )
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for Value<'a> {
+    type Deserializer = Value<'a>;
+
+    fn into_deserializer(self) -> Value<'a> {
+        self
+    }
+}
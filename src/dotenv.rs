@@ -0,0 +1,150 @@
+//! Loading of `.env` files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Reads the `.env` file at `path` and sets every variable it defines that isn't already set.
+///
+/// Lines are `KEY=value` pairs, optionally preceded by `export`. Blank lines and lines starting
+/// with `#` are ignored. Values can be wrapped in single quotes, which are taken literally, or
+/// double quotes, which understand `\n`, `\t`, `\"` and `\\` escapes. Unquoted values end at the
+/// first ` #`, so they can carry a trailing comment.
+///
+/// A missing file is not an error, matching the behavior of other dotenv tools.
+pub fn load(path: impl AsRef<Path>) -> io::Result<()> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for (key, value) in parse(&contents)? {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the `.env` file at `path` into a map, without touching the process environment.
+///
+/// The map is an [`EnvSource`](crate::EnvSource), so it can be layered under the environment
+/// with [`Layered`](crate::Layered). When a variable is defined more than once, the last definition wins.
+/// A missing file yields an empty map.
+pub fn read(path: impl AsRef<Path>) -> io::Result<HashMap<String, String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(parse(&contents)?.into_iter().collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parses the contents of a `.env` file into its `(key, value)` pairs, in file order.
+///
+/// ```rust
+/// let vars = envar::dotenv::parse("# database\nexport HOST=localhost # local only\nNAME=\"my app\"\n").unwrap();
+/// assert_eq!(vars, vec![
+///     ("HOST".to_string(), "localhost".to_string()),
+///     ("NAME".to_string(), "my app".to_string()),
+/// ]);
+/// ```
+pub fn parse(contents: &str) -> io::Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", index + 1, message),
+            )
+        };
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid("expected KEY=value"))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(invalid("missing variable name"));
+        }
+        let value = parse_value(value.trim()).ok_or_else(|| invalid("unterminated quote"))?;
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some(rest[..end].to_string());
+    }
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(parsed),
+                '\\' => match chars.next()? {
+                    'n' => parsed.push('\n'),
+                    't' => parsed.push('\t'),
+                    other => parsed.push(other),
+                },
+                other => parsed.push(other),
+            }
+        }
+        return None;
+    }
+    let value = match value.find(" #") {
+        Some(comment) => &value[..comment],
+        None => value,
+    };
+    Some(value.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unquoted_values_end_at_a_comment() {
+        assert_eq!(
+            parse_value("localhost # local only"),
+            Some("localhost".to_string())
+        );
+        assert_eq!(parse_value("a#b"), Some("a#b".to_string()));
+        assert_eq!(parse_value(""), Some(String::new()));
+    }
+
+    #[test]
+    fn single_quoted_values_are_literal() {
+        assert_eq!(parse_value(r"'a\n b # c'"), Some(r"a\n b # c".to_string()));
+    }
+
+    #[test]
+    fn double_quoted_values_understand_escapes() {
+        assert_eq!(
+            parse_value(r#""line\n\tnext \"quoted\" \\""#),
+            Some("line\n\tnext \"quoted\" \\".to_string())
+        );
+    }
+
+    #[test]
+    fn unterminated_quotes_are_rejected() {
+        assert_eq!(parse_value("'open"), None);
+        assert_eq!(parse_value("\"open"), None);
+        assert_eq!(parse_value(r#""escaped end\""#), None);
+    }
+
+    #[test]
+    fn parse_reports_the_failing_line() {
+        let err = parse("HOST=localhost\nPORT\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected KEY=value");
+        let err = parse("=value").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: missing variable name");
+        let err = parse("NAME=\"my app").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: unterminated quote");
+    }
+}
@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+
+/// An error produced while reading a struct from the environment.
+#[derive(Debug)]
+pub enum EnvarError {
+    /// A required environment variable was not set.
+    Missing { var: String },
+    /// A required environment variable was not set, and its field gave a message with `env(error = "...")`.
+    Custom { var: String, message: String },
+    /// An environment variable was set, but its value is not valid UTF-8.
+    NotUnicode { var: String, value: OsString },
+    /// An environment variable was set but its value could not be parsed.
+    ///
+    /// `value` is the text that failed to parse, which is an element of the value for collections.
+    /// For secret fields every character of it is replaced by `*`, so only its length shows.
+    Parse {
+        var: String,
+        value: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// A variable was parsed but failed validation.
+    Invalid { var: String, reason: String },
+    /// A variable referenced another variable with `${...}` that is not set.
+    Expand { var: String, reference: String },
+    /// A variable pointed to a file that could not be read.
+    File {
+        var: String,
+        path: String,
+        source: io::Error,
+    },
+    /// A `.env` file exists but could not be read.
+    Dotenv { path: String, source: io::Error },
+    /// Several variables were missing or invalid.
+    Multiple(Vec<EnvarError>),
+}
+
+impl From<Vec<EnvarError>> for EnvarError {
+    fn from(mut errors: Vec<EnvarError>) -> Self {
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            EnvarError::Multiple(errors)
+        }
+    }
+}
+
+impl fmt::Display for EnvarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvarError::Missing { var } => write!(f, "Environment variable {} not found", var),
+            EnvarError::Parse { var, value, source } => {
+                write!(
+                    f,
+                    "Failed to parse environment variable {}={:?}: {}",
+                    var, value, source
+                )
+            }
+            EnvarError::NotUnicode { var, value } => write!(
+                f,
+                "Environment variable {} is not valid UTF-8: {:?}",
+                var, value
+            ),
+            EnvarError::Custom { message, .. } => f.write_str(message),
+            EnvarError::Invalid { var, reason } => write!(f, "{} {}", var, reason),
+            EnvarError::Expand { var, reference } => write!(
+                f,
+                "Environment variable {} references {}, which is not set",
+                var, reference
+            ),
+            EnvarError::File { var, path, source } => {
+                write!(f, "Failed to read {} from {}: {}", var, path, source)
+            }
+            EnvarError::Dotenv { path, source } => {
+                write!(f, "Failed to load {}: {}", path, source)
+            }
+            EnvarError::Multiple(errors) => {
+                write!(f, "{} environment variable errors:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for EnvarError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EnvarError::Missing { .. }
+            | EnvarError::Custom { .. }
+            | EnvarError::NotUnicode { .. }
+            | EnvarError::Invalid { .. }
+            | EnvarError::Expand { .. }
+            | EnvarError::Multiple(_) => None,
+            EnvarError::Parse { source, .. } => Some(source.as_ref()),
+            EnvarError::File { source, .. } | EnvarError::Dotenv { source, .. } => Some(source),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::EnvarError;
+
+/// Types that can be read from the process environment.
+///
+/// `#[derive(Envar)]` implements this next to the inherent `try_new()`, so generic code
+/// can load any config type.
+/// ```rust
+/// use envar::{Envar, EnvarError, FromEnv};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "FROM_ENV_EXAMPLE_PORT"]
+///   port: u16,
+///}
+/// fn load<C: FromEnv>() -> Result<C, EnvarError> {
+///     C::from_env()
+/// }
+/// # std::env::set_var("FROM_ENV_EXAMPLE_PORT", "8080");
+/// let config: Config = load().unwrap();
+/// assert_eq!(config.port, 8080);
+/// ```
+pub trait FromEnv: Sized {
+    /// Reads the value from the environment, like the derived `try_new()`.
+    fn from_env() -> Result<Self, EnvarError>;
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+
+use crate::__private::Global;
+use crate::EnvarError;
+
+/// A field that is parsed the first time it is used rather than when the config is read.
+///
+/// The variable is still looked up with the rest of the config, so the value doesn't change
+/// afterwards, but parsing it and reporting a missing or malformed value wait for
+/// [`get`](Lazy::get) or [`try_get`](Lazy::try_get). Once parsed, the value is kept.
+/// To defer the lookup as well, use an `env(lazy)` field instead.
+/// ```rust
+/// use envar::{Envar, Lazy};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "LAZY_TYPE_EXAMPLE_DATABASE_URL"]
+///   database_url: Lazy<String>,
+///}
+/// # std::env::set_var("LAZY_TYPE_EXAMPLE_DATABASE_URL", "postgres://localhost");
+/// let config = Config::new();
+/// assert_eq!(config.database_url.get(), "postgres://localhost");
+/// ```
+pub struct Lazy<T> {
+    value: Global<T>,
+    read: Box<dyn Fn() -> Result<T, EnvarError> + Send + Sync>,
+}
+
+impl<T> Lazy<T> {
+    /// Creates a value produced by `read` on first use.
+    pub fn new(read: impl Fn() -> Result<T, EnvarError> + Send + Sync + 'static) -> Self {
+        Lazy {
+            value: Global::new(),
+            read: Box::new(read),
+        }
+    }
+
+    /// Returns the value, parsing it first if this is the first use.
+    /// A failure is returned as is, and the next call tries again.
+    pub fn try_get(&self) -> Result<&T, EnvarError> {
+        self.value.get_or_try_init(|| (self.read)())
+    }
+
+    /// Like [`try_get`](Lazy::try_get), but panics if the value can't be read.
+    pub fn get(&self) -> &T {
+        match self.try_get() {
+            Ok(value) => value,
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => value.fmt(f),
+            None => f.write_str("<unparsed>"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => f.write_str("Lazy(<unparsed>)"),
+        }
+    }
+}
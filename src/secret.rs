@@ -0,0 +1,94 @@
+use std::fmt;
+use std::ops::Deref;
+
+/// A value that is never printed, such as a password or an API token.
+///
+/// `Debug` and `Display` both print `***`, and the value is reached with
+/// [`expose_secret`](Secret::expose_secret) or through `Deref`.
+/// With the `zeroize` feature enabled, the value is wiped from memory when the secret is dropped,
+/// which requires `T` to implement `zeroize::Zeroize`.
+///
+/// Fields of type `Secret<T>` are parsed as a `T` and then wrapped.
+/// ```rust
+/// use envar::{Envar, Secret};
+/// #[derive(Envar)]
+/// struct Config {
+///   #[env = "SECRET_TYPE_EXAMPLE_PASSWORD"]
+///   password: Secret<String>,
+///}
+/// # std::env::set_var("SECRET_TYPE_EXAMPLE_PASSWORD", "hunter2");
+/// let config = Config::new();
+/// assert_eq!(config.password.expose_secret(), "hunter2");
+/// assert_eq!(format!("{:?}", config.password), "***");
+/// ```
+pub struct Secret<T: Wipe>(T);
+
+impl<T: Wipe> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// Returns the wrapped value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Secret(self.0.clone())
+    }
+}
+
+impl<T: Wipe> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Wipe> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T: Wipe> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+/// Values that can be held in a [`Secret`].
+///
+/// Every type qualifies, unless the `zeroize` feature is enabled, in which case
+/// only types implementing `zeroize::Zeroize` do.
+pub trait Wipe {
+    /// Clears the value before it is dropped.
+    fn wipe(&mut self);
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T: ?Sized> Wipe for T {
+    fn wipe(&mut self) {}
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize + ?Sized> Wipe for T {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::hash::BuildHasher;
+
+/// A place environment variables are read from.
+///
+/// The derived `new()`, `try_new()` and `collect()` read from [`StdEnv`], the process environment.
+/// `from_source()` accepts any other implementation, which makes configs easy to test
+/// without touching the global environment.
+///
+/// ```rust
+/// use envar::Envar;
+/// use std::collections::HashMap;
+/// #[derive(Envar)]
+/// struct Config {
+///   port: u16,
+///}
+/// let mut vars = HashMap::new();
+/// vars.insert("PORT".to_string(), "8080".to_string());
+/// let config = Config::from_source(&vars).unwrap();
+/// assert_eq!(config.port, 8080);
+/// ```
+pub trait EnvSource {
+    /// Returns the value of `key`, or `None` if it isn't set.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Returns the raw value of `key`, which may not be valid UTF-8.
+    ///
+    /// Used for `OsString` fields. The default implementation converts the result of [`get`](EnvSource::get).
+    fn get_os(&self, key: &str) -> Option<OsString> {
+        self.get(key).map(OsString::from)
+    }
+}
+
+/// The process environment, as seen by [`std::env::var`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdEnv;
+
+impl EnvSource for StdEnv {
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+
+    fn get_os(&self, key: &str) -> Option<OsString> {
+        std::env::var_os(key)
+    }
+}
+
+/// A copy of the process environment taken at one point in time.
+///
+/// The derived `from_env_snapshot()` reads through this, so that a `set_var` from another thread
+/// while the struct is being read can't leave it with a mix of old and new values.
+///
+/// ```rust
+/// use envar::{EnvSource, Snapshot};
+/// std::env::set_var("SNAPSHOT_EXAMPLE_PORT", "8080");
+/// let snapshot = Snapshot::capture();
+/// std::env::set_var("SNAPSHOT_EXAMPLE_PORT", "9090");
+/// assert_eq!(snapshot.get("SNAPSHOT_EXAMPLE_PORT").as_deref(), Some("8080"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    vars: HashMap<OsString, OsString>,
+}
+
+impl Snapshot {
+    /// Copies every variable of the process environment.
+    pub fn capture() -> Self {
+        Snapshot {
+            vars: std::env::vars_os().collect(),
+        }
+    }
+}
+
+impl EnvSource for Snapshot {
+    fn get(&self, key: &str) -> Option<String> {
+        self.get_os(key)?.into_string().ok()
+    }
+
+    fn get_os(&self, key: &str) -> Option<OsString> {
+        self.vars.get(OsStr::new(key)).cloned()
+    }
+}
+
+impl<S: BuildHasher> EnvSource for HashMap<String, String, S> {
+    fn get(&self, key: &str) -> Option<String> {
+        HashMap::get(self, key).cloned()
+    }
+}
+
+/// Several sources consulted in order, where the first one that has a variable wins.
+///
+/// The derived `collect()` uses this to read the process environment first,
+/// then the `.env` file named by `envar(dotenv = "...")`.
+///
+/// ```rust
+/// use envar::{EnvSource, Layered};
+/// use std::collections::HashMap;
+/// let overrides = HashMap::from([("PORT".to_string(), "9090".to_string())]);
+/// let base = HashMap::from([
+///     ("PORT".to_string(), "8080".to_string()),
+///     ("HOST".to_string(), "localhost".to_string()),
+/// ]);
+/// let source = Layered::new().with(&overrides).with(&base);
+/// assert_eq!(source.get("PORT").as_deref(), Some("9090"));
+/// assert_eq!(source.get("HOST").as_deref(), Some("localhost"));
+/// ```
+#[derive(Clone, Default)]
+pub struct Layered<'a> {
+    sources: Vec<&'a dyn EnvSource>,
+}
+
+impl<'a> Layered<'a> {
+    /// Creates an empty stack of sources.
+    pub fn new() -> Self {
+        Layered::default()
+    }
+
+    /// Adds `source` below the ones added before it.
+    pub fn with(mut self, source: &'a dyn EnvSource) -> Self {
+        self.sources.push(source);
+        self
+    }
+}
+
+impl<'a> FromIterator<&'a dyn EnvSource> for Layered<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a dyn EnvSource>>(sources: I) -> Self {
+        Layered {
+            sources: sources.into_iter().collect(),
+        }
+    }
+}
+
+impl EnvSource for Layered<'_> {
+    fn get(&self, key: &str) -> Option<String> {
+        self.sources.iter().find_map(|source| source.get(key))
+    }
+
+    fn get_os(&self, key: &str) -> Option<OsString> {
+        self.sources.iter().find_map(|source| source.get_os(key))
+    }
+}
+
+/// Another source seen through a prefix, so that reading `PORT` looks up `<prefix>PORT`.
+///
+/// The derived `from_env_with_prefix()` reads the process environment through this,
+/// which lets several instances of a config share one process.
+///
+/// ```rust
+/// use envar::{EnvSource, Prefixed};
+/// use std::collections::HashMap;
+/// let vars = HashMap::from([("INSTANCE1_PORT".to_string(), "8080".to_string())]);
+/// let source = Prefixed::new("INSTANCE1_", &vars);
+/// assert_eq!(source.get("PORT").as_deref(), Some("8080"));
+/// ```
+pub struct Prefixed<'a, S: EnvSource + ?Sized = dyn EnvSource + 'a> {
+    prefix: &'a str,
+    source: &'a S,
+}
+
+impl<'a, S: EnvSource + ?Sized> Prefixed<'a, S> {
+    /// Reads `source` with `prefix` put in front of every name.
+    pub fn new(prefix: &'a str, source: &'a S) -> Self {
+        Prefixed { prefix, source }
+    }
+}
+
+impl<S: EnvSource + ?Sized> Clone for Prefixed<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: EnvSource + ?Sized> Copy for Prefixed<'_, S> {}
+
+impl<S: EnvSource + ?Sized> EnvSource for Prefixed<'_, S> {
+    fn get(&self, key: &str) -> Option<String> {
+        self.source.get(&format!("{}{}", self.prefix, key))
+    }
+
+    fn get_os(&self, key: &str) -> Option<OsString> {
+        self.source.get_os(&format!("{}{}", self.prefix, key))
+    }
+}
@@ -0,0 +1,22 @@
+#[test]
+fn negative_number_defaults() {
+    let config = envar::config! {
+        CONFIG_TEST_OFFSET: i64 = -42,
+        CONFIG_TEST_SCALE: f64 = -0.5,
+        CONFIG_TEST_BIAS: i8 = -128,
+    }
+    .unwrap();
+    assert_eq!(config.config_test_offset, -42);
+    assert_eq!(config.config_test_scale, -0.5);
+    assert_eq!(config.config_test_bias, i8::MIN);
+}
+
+#[test]
+fn negative_number_defaults_give_way_to_the_environment() {
+    std::env::set_var("CONFIG_TEST_SET_OFFSET", "7");
+    let config = envar::config! {
+        CONFIG_TEST_SET_OFFSET: i32 = -1,
+    }
+    .unwrap();
+    assert_eq!(config.config_test_set_offset, 7);
+}
@@ -0,0 +1,95 @@
+use envar::{Envar, EnvarError};
+use std::time::Duration;
+
+#[derive(Envar, Debug)]
+struct Port {
+    #[env = "ERRORS_TEST_PORT"]
+    port: u16,
+}
+
+#[test]
+fn missing_and_malformed_values() {
+    std::env::remove_var("ERRORS_TEST_PORT");
+    assert!(matches!(
+        Port::try_new(),
+        Err(EnvarError::Missing { var }) if var == "ERRORS_TEST_PORT"
+    ));
+    std::env::set_var("ERRORS_TEST_PORT", "not a number");
+    assert!(matches!(Port::try_new(), Err(EnvarError::Parse { .. })));
+    std::env::set_var("ERRORS_TEST_PORT", "8080");
+    assert_eq!(Port::new().port, 8080);
+}
+
+#[derive(Envar, Debug)]
+struct Pin {
+    #[env(name = "ERRORS_TEST_PIN", secret)]
+    pin: u32,
+}
+
+#[test]
+fn secret_values_are_masked() {
+    std::env::set_var("ERRORS_TEST_PIN", "1234");
+    assert_eq!(Pin::new().pin, 1234);
+    std::env::set_var("ERRORS_TEST_PIN", "99999999999");
+    assert_eq!(
+        Pin::try_new().unwrap_err().to_string(),
+        "ERRORS_TEST_PIN value *********** is out of range for u32"
+    );
+    std::env::set_var("ERRORS_TEST_PIN", "-1");
+    assert_eq!(
+        Pin::try_new().unwrap_err().to_string(),
+        "Failed to parse environment variable ERRORS_TEST_PIN=\"**\": invalid digit found in string"
+    );
+}
+
+#[derive(Envar, Debug)]
+struct Verbosity {
+    #[env = "ERRORS_TEST_VERBOSITY"]
+    #[env(count)]
+    verbosity: u8,
+}
+
+#[test]
+fn counts_that_overflow_are_errors() {
+    std::env::set_var("ERRORS_TEST_VERBOSITY", "vvv");
+    assert_eq!(Verbosity::new().verbosity, 3);
+    std::env::set_var("ERRORS_TEST_VERBOSITY", "v".repeat(300));
+    assert!(Verbosity::try_new().is_err());
+    std::env::set_var("ERRORS_TEST_VERBOSITY", "99999999999999999999999");
+    assert_eq!(
+        Verbosity::try_new().unwrap_err().to_string(),
+        "ERRORS_TEST_VERBOSITY value 99999999999999999999999 is out of range for usize"
+    );
+}
+
+#[derive(Envar, Debug)]
+struct Timeouts {
+    #[env = "ERRORS_TEST_TIMEOUT"]
+    timeout: Duration,
+    #[env = "ERRORS_TEST_TIMEOUT_SECS"]
+    #[env(secs)]
+    timeout_secs: Duration,
+}
+
+#[test]
+fn durations_that_are_negative_or_too_large_are_errors() {
+    std::env::set_var("ERRORS_TEST_TIMEOUT", "5000000000ns");
+    std::env::set_var("ERRORS_TEST_TIMEOUT_SECS", "30");
+    let timeouts = Timeouts::new();
+    assert_eq!(timeouts.timeout, Duration::from_secs(5));
+    assert_eq!(timeouts.timeout_secs, Duration::from_secs(30));
+
+    std::env::set_var("ERRORS_TEST_TIMEOUT", "18446744073709551615d");
+    std::env::set_var("ERRORS_TEST_TIMEOUT_SECS", "-5");
+    let EnvarError::Multiple(errors) = Timeouts::try_new().unwrap_err() else {
+        panic!("expected both fields to fail");
+    };
+    assert_eq!(
+        errors[0].to_string(),
+        "Failed to parse environment variable ERRORS_TEST_TIMEOUT=\"18446744073709551615d\": duration is too large"
+    );
+    assert_eq!(
+        errors[1].to_string(),
+        "Failed to parse environment variable ERRORS_TEST_TIMEOUT_SECS=\"-5\": a duration can't be negative"
+    );
+}
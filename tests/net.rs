@@ -0,0 +1,85 @@
+use envar::{Envar, EnvarError};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+#[derive(Envar, Debug)]
+struct Addresses {
+    #[env = "NET_TEST_SOCKET"]
+    socket: SocketAddr,
+    #[env = "NET_TEST_SOCKET_V4"]
+    socket_v4: SocketAddrV4,
+    #[env = "NET_TEST_SOCKET_V6"]
+    socket_v6: SocketAddrV6,
+    #[env = "NET_TEST_IP"]
+    ip: IpAddr,
+    #[env = "NET_TEST_IPV4"]
+    ipv4: Ipv4Addr,
+    #[env = "NET_TEST_IPV6"]
+    ipv6: Ipv6Addr,
+    #[env = "NET_TEST_OPTIONAL"]
+    optional: Option<SocketAddr>,
+}
+
+fn parse_errors(err: EnvarError) -> Vec<(String, String)> {
+    let errors = match err {
+        EnvarError::Multiple(errors) => errors,
+        err => vec![err],
+    };
+    errors
+        .into_iter()
+        .map(|err| match err {
+            EnvarError::Parse { var, source, .. } => (var, source.to_string()),
+            err => panic!("expected a parse error, got {}", err),
+        })
+        .collect()
+}
+
+// Every test reads the same variables, so they run as one test
+#[test]
+fn addresses() {
+    std::env::set_var("NET_TEST_SOCKET", "[::1]:8080");
+    std::env::set_var("NET_TEST_SOCKET_V4", "127.0.0.1:80");
+    std::env::set_var("NET_TEST_SOCKET_V6", "[2001:db8::1]:443");
+    std::env::set_var("NET_TEST_IP", "192.168.0.1");
+    std::env::set_var("NET_TEST_IPV4", "10.0.0.1");
+    std::env::set_var("NET_TEST_IPV6", "::1");
+    std::env::remove_var("NET_TEST_OPTIONAL");
+    let addresses = Addresses::new();
+    assert_eq!(addresses.socket, "[::1]:8080".parse().unwrap());
+    assert_eq!(
+        addresses.socket_v4,
+        SocketAddrV4::new(Ipv4Addr::LOCALHOST, 80)
+    );
+    assert_eq!(addresses.socket_v6, "[2001:db8::1]:443".parse().unwrap());
+    assert_eq!(addresses.ip, IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+    assert_eq!(addresses.ipv4, Ipv4Addr::new(10, 0, 0, 1));
+    assert_eq!(addresses.ipv6, Ipv6Addr::LOCALHOST);
+    assert_eq!(addresses.optional, None);
+
+    std::env::set_var("NET_TEST_OPTIONAL", "0.0.0.0:9090");
+    assert_eq!(
+        Addresses::new().optional,
+        Some("0.0.0.0:9090".parse().unwrap())
+    );
+
+    // Each kind of address says what it expected
+    std::env::set_var("NET_TEST_SOCKET", "localhost:8080");
+    std::env::set_var("NET_TEST_SOCKET_V4", "[::1]:80");
+    std::env::set_var("NET_TEST_SOCKET_V6", "127.0.0.1:443");
+    std::env::set_var("NET_TEST_IP", "192.168.0.256");
+    std::env::set_var("NET_TEST_IPV4", "::1");
+    std::env::set_var("NET_TEST_IPV6", "10.0.0.1");
+    std::env::set_var("NET_TEST_OPTIONAL", "0.0.0.0");
+    assert_eq!(
+        parse_errors(Addresses::try_new().unwrap_err()),
+        [
+            ("NET_TEST_SOCKET", "not a valid socket address"),
+            ("NET_TEST_SOCKET_V4", "not a valid IPv4 socket address"),
+            ("NET_TEST_SOCKET_V6", "not a valid IPv6 socket address"),
+            ("NET_TEST_IP", "not a valid IP address"),
+            ("NET_TEST_IPV4", "not a valid IPv4 address"),
+            ("NET_TEST_IPV6", "not a valid IPv6 address"),
+            ("NET_TEST_OPTIONAL", "not a valid socket address"),
+        ]
+        .map(|(var, reason)| (var.to_string(), reason.to_string()))
+    );
+}